@@ -3,10 +3,12 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use cw20_dao_crowdfund::msg::DumpStateResponse;
-use cw20_dao_crowdfund::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use cw20_dao_crowdfund::state::Campaign;
+use cw20_dao_crowdfund::msg::{MilestonesResponse, VestingResponse};
+use cw20_dao_crowdfund::msg::{ContributorsResponse, DumpStateResponse};
+use cw20_dao_crowdfund::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use cw20_dao_crowdfund::msg::{SimulateFundResponse, SimulateRefundResponse, SimulateSwapResponse};
 use cw20_dao_crowdfund::state::State;
+use cw20_dao_crowdfund::state::{Campaign, Contribution};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -17,7 +19,15 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(DumpStateResponse), &out_dir);
+    export_schema(&schema_for!(ContributorsResponse), &out_dir);
+    export_schema(&schema_for!(Contribution), &out_dir);
+    export_schema(&schema_for!(SimulateFundResponse), &out_dir);
+    export_schema(&schema_for!(SimulateRefundResponse), &out_dir);
+    export_schema(&schema_for!(SimulateSwapResponse), &out_dir);
+    export_schema(&schema_for!(VestingResponse), &out_dir);
+    export_schema(&schema_for!(MilestonesResponse), &out_dir);
     export_schema(&schema_for!(Campaign), &out_dir);
 }
@@ -1,22 +1,45 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, BankMsg, Binary, Coin, Decimal, Deps, DepsMut, Env, Fraction, MessageInfo,
-    Reply, Response, StdResult, SubMsg, Uint128, WasmMsg,
+    from_binary, to_binary, Addr, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Order, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
-use cw20::Cw20ReceiveMsg;
+use cw20::{Balance, Cw20CoinVerified, Cw20ReceiveMsg, Denom};
+use cw_storage_plus::Bound;
 use cw_utils::parse_reply_instantiate_data;
 
 use crate::error::ContractError;
-use crate::msg::{DumpStateResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Campaign, Status};
-use crate::state::{State, FUNDING_TOKEN_ADDR, GOV_TOKEN_ADDR, STATE};
+use crate::milestones::{MilestoneClaim, VetoRefunds};
+use crate::msg::{
+    BeneficiaryMsg, ContributorResponse, ContributorsResponse, DumpStateResponse, ExecuteMsg,
+    InstantiateMsg, MigrateMsg, MilestonesResponse, Payout, PresaleProof, QueryMsg, ReceiveMsg,
+    RegistryExecuteMsg, SimulateFundResponse, SimulateRefundResponse, SimulateSwapResponse,
+    VestingResponse,
+};
+use crate::poll::{LockedVote, Poll, PollKind};
+use crate::presale::{validate_merkle_root, verify_proof};
+use crate::pricing::{
+    bonus_at, max_bonus, mul_decimal, mul_ratio, sale_reserve, validate_bonus_windows,
+};
+use crate::state::{Beneficiary, Campaign, Status};
+use crate::state::{
+    State, Vesting, CONTRIBUTIONS, FUNDING_TOKEN_ADDR, GOV_TOKEN_ADDR, MILESTONE_PROGRESS, POLL,
+    REFUND_CURSOR, STATE, VESTING, VOTES, VOTES_LOCKED,
+};
 
-const CONTRACT_NAME: &str = "crates.io:cw20-dao-crowdfund";
-const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub(crate) const CONTRACT_NAME: &str = "crates.io:cw20-dao-crowdfund";
+pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const INSTANTIATE_FUNDING_TOKEN_REPLY_ID: u64 = 0;
+const REGISTRY_CALLBACK_REPLY_ID: u64 = 1;
+
+/// Seconds after a campaign is funded that the DAO may settle it
+/// even if funding tokens have not all been exchanged.
+const SETTLEMENT_GRACE_PERIOD: u64 = 60 * 60 * 24 * 365;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -28,17 +51,16 @@ pub fn instantiate(
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     let dao_addr = deps.api.addr_validate(&msg.dao_address)?;
-    let dao_config: cw3_dao::query::ConfigResponse = deps
-        .querier
-        .query_wasm_smart(dao_addr.clone(), &cw3_dao::msg::QueryMsg::GetConfig {})?;
+    let dao_type = msg.dao_type.unwrap_or_default();
+    let gov_token = dao_type.adapter().gov_token(&deps.querier, &dao_addr)?;
 
     // Verify fee manager.
     let fee_manager_addr = deps.api.addr_validate(&msg.fee_manager_address)?;
-    get_fee_manager_config(&deps, &fee_manager_addr)?;
+    let fee_manager_config = get_fee_manager_config(deps.as_ref(), &fee_manager_addr)?;
 
     // DAO shouldn't have an invalid gov token address but lets verify
     // just to be sure.
-    let gov_token_addr = deps.api.addr_validate(dao_config.gov_token.as_str())?;
+    let gov_token_addr = deps.api.addr_validate(gov_token.as_str())?;
     GOV_TOKEN_ADDR.save(deps.storage, &gov_token_addr)?;
 
     if msg.funding_goal.amount == Uint128::zero() {
@@ -48,16 +70,122 @@ pub fn instantiate(
         )));
     }
 
+    let min_goal = msg.min_goal.unwrap_or(msg.funding_goal.amount);
+    if min_goal.is_zero() || min_goal > msg.funding_goal.amount {
+        return Err(ContractError::Instantiation(format!(
+            "minimum goal ({}) must be non-zero and no greater than the funding goal ({})",
+            min_goal, msg.funding_goal.amount
+        )));
+    }
+
+    let funding_denom = match &msg.funding_cw20 {
+        Some(address) => {
+            let address = deps.api.addr_validate(address)?;
+            if address != msg.funding_goal.denom {
+                return Err(ContractError::Instantiation(format!(
+                    "funding goal denom ({}) must be the funding cw20 address ({})",
+                    msg.funding_goal.denom, address
+                )));
+            }
+            if address == gov_token_addr {
+                return Err(ContractError::Instantiation(
+                    "campaigns can not raise funds in their own governance token".to_string(),
+                ));
+            }
+            // Verify that the funding asset is a cw20 token.
+            deps.querier
+                .query_wasm_smart::<cw20::TokenInfoResponse>(
+                    address.clone(),
+                    &cw20::Cw20QueryMsg::TokenInfo {},
+                )
+                .map_err(|e| {
+                    ContractError::Instantiation(format!("invalid funding cw20: ({})", e))
+                })?;
+            Denom::Cw20(address)
+        }
+        None => Denom::Native(msg.funding_goal.denom.clone()),
+    };
+
+    let pricing = msg.pricing.unwrap_or_default();
+    pricing.validate(msg.funding_goal.amount)?;
+    let bonus_windows = msg.bonus_windows.unwrap_or_default();
+    validate_bonus_windows(&bonus_windows)?;
+
+    if let Some(presale) = &msg.presale {
+        validate_merkle_root(&presale.merkle_root)?;
+    }
+    if let Some(vesting) = &msg.vesting {
+        vesting.validate()?;
+    }
+    if let Some(milestones) = &msg.milestones {
+        milestones.validate()?;
+    }
+    if let Some(cancellation) = &msg.cancellation {
+        cancellation.validate()?;
+    }
+    let beneficiaries =
+        validate_beneficiaries(deps.as_ref(), msg.beneficiaries.unwrap_or_default())?;
+    // Only the registry creating the campaign may list it. Other
+    // registries would reject its callbacks.
+    let registry = match msg.registry {
+        Some(registry) if deps.api.addr_validate(&registry)? == info.sender => {
+            Some(info.sender.clone())
+        }
+        Some(_) => {
+            return Err(ContractError::Instantiation(
+                "registry must be the contract instantiating the campaign".to_string(),
+            ))
+        }
+        None => None,
+    };
+
+    if let Some(max) = msg.max_contribution_per_address {
+        if max.is_zero() || max < msg.min_contribution.unwrap_or_default() {
+            return Err(ContractError::Instantiation(format!(
+                "maximum contribution per address ({}) must be non-zero and no less than the minimum contribution",
+                max
+            )));
+        }
+    }
+
+    if let Some(deadline) = &msg.deadline {
+        if deadline.is_expired(&env.block) {
+            return Err(ContractError::Instantiation(format!(
+                "deadline has already passed ({})",
+                deadline
+            )));
+        }
+    }
+
     let state = State {
         status: Status::Uninstantiated {},
         dao_addr,
+        dao_type,
+        beneficiaries,
         fee_manager_addr: fee_manager_addr.clone(),
+        fee: fee_manager_config.fee,
+        fee_receiver: fee_manager_config.fee_receiver,
         creator: info.sender.clone(),
+        funding_denom,
         funding_goal: msg.funding_goal.clone(),
+        min_goal,
+        pricing,
+        bonus_windows,
+        min_contribution: msg.min_contribution,
+        max_contribution_per_address: msg.max_contribution_per_address,
+        presale: msg.presale,
+        vesting: msg.vesting,
+        milestones: msg.milestones,
+        cancellation: msg.cancellation,
+        registry,
         funds_raised: Coin {
             denom: msg.funding_goal.denom,
             amount: Uint128::zero(),
         },
+        unrecorded_contributions: false,
+        deadline: msg.deadline,
+        opened_at: None,
+        funded_at: None,
         campaign_info: msg.campaign_info.clone(),
     };
     STATE.save(deps.storage, &state)?;
@@ -102,17 +230,206 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let before = STATE.load(deps.storage)?;
+    let response = execute_msg(deps.branch(), env, info, msg)?;
+    notify_registry(deps.as_ref(), &before, response)
+}
+
+fn execute_msg(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Fund {} => execute_fund(deps, &info.funds, info.sender),
-        ExecuteMsg::Receive(msg) => execute_receive(deps, msg, info.sender),
+        ExecuteMsg::Fund {} => {
+            execute_fund(deps, env, info.sender, Balance::from(info.funds), None)
+        }
+        ExecuteMsg::FundPresale { allocation, proof } => execute_fund(
+            deps,
+            env,
+            info.sender,
+            Balance::from(info.funds),
+            Some(PresaleProof { allocation, proof }),
+        ),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, msg, info.sender),
         ExecuteMsg::Close {} => execute_close(deps, env, info.sender),
+        ExecuteMsg::Abort {} => execute_abort(deps, env, info.sender),
         ExecuteMsg::UpdateCampaign { campaign } => execute_update_campaign(deps, info, campaign),
+        ExecuteMsg::Finalize {} => execute_finalize(deps, env, info.sender),
+        ExecuteMsg::Settle {} => execute_settle(deps, env, info.sender),
+        ExecuteMsg::ProcessRefunds { limit } => execute_process_refunds(deps, env, limit),
+        ExecuteMsg::AcceptFeeReduction {} => execute_accept_fee_reduction(deps, info.sender),
+        ExecuteMsg::UpdatePresaleMerkleRoot { merkle_root } => {
+            execute_update_presale_merkle_root(deps, env, info.sender, merkle_root)
+        }
+        ExecuteMsg::ClaimVested {} => execute_claim_vested(deps, env, info.sender),
+        ExecuteMsg::UpdateBeneficiaries { beneficiaries } => {
+            execute_update_beneficiaries(deps, info.sender, beneficiaries)
+        }
+        ExecuteMsg::ClaimMilestone {} => execute_claim_milestone(deps, env, info.sender),
+        ExecuteMsg::ReleaseMilestone {} => execute_release_milestone(deps, env),
+        ExecuteMsg::WithdrawVote {} => execute_withdraw_vote(deps, env, info.sender),
+        ExecuteMsg::OpenCancellationPoll {} => {
+            execute_open_cancellation_poll(deps, env, info.sender)
+        }
+    }
+}
+
+/// Reports changes to the campaign's status or visibility since
+/// `before` to its registry. Open campaigns whose deadline has passed
+/// are reported once they are finalized. Failed callbacks are
+/// ignored so that the registry can never block the campaign.
+fn notify_registry(
+    deps: Deps,
+    before: &State,
+    response: Response,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    match state.registry {
+        Some(registry)
+            if state.status != before.status
+                || state.campaign_info.hidden != before.campaign_info.hidden =>
+        {
+            Ok(response.add_submessage(SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: registry.to_string(),
+                    msg: to_binary(&RegistryExecuteMsg::CampaignUpdated {
+                        status: state.status,
+                        hidden: state.campaign_info.hidden,
+                    })?,
+                    funds: vec![],
+                },
+                REGISTRY_CALLBACK_REPLY_ID,
+            )))
+        }
+        _ => Ok(response),
+    }
+}
+
+/// Gets the status of the campaign at `block`. Contracts can not
+/// execute transactions on their own so an open campaign whose
+/// deadline has passed is resolved lazily.
+pub fn current_status(state: &State, block: &BlockInfo) -> Status {
+    match state.status {
+        Status::Open { .. } if deadline_passed(state, block) => resolve_status(state),
+        ref status => status.clone(),
+    }
+}
+
+/// Gets the status an open campaign resolves to once it stops
+/// accepting funds. Campaigns that have met their minimum goal are
+/// funded, all others expire.
+fn resolve_status(state: &State) -> Status {
+    match state.status {
+        Status::Open {
+            initial_gov_token_balance,
+        } if state.funds_raised.amount >= state.min_goal => Status::Funded {
+            initial_gov_token_balance,
+        },
+        Status::Open {
+            initial_gov_token_balance,
+        } => Status::Expired {
+            initial_gov_token_balance,
+        },
+        ref status => status.clone(),
+    }
+}
+
+/// Moves the campaign to `status`, recording when it was funded.
+fn set_status(state: &mut State, status: Status, block: &BlockInfo) {
+    if matches!(status, Status::Funded { .. }) && state.funded_at.is_none() {
+        state.funded_at = Some(block.time);
     }
+    state.status = status;
+}
+
+fn deadline_passed(state: &State, block: &BlockInfo) -> bool {
+    matches!(&state.deadline, Some(deadline) if deadline.is_expired(block))
+}
+
+/// Creates a message sending `amount` of the campaign's funding
+/// asset to `recipient`.
+fn transfer_funds(
+    denom: &Denom,
+    recipient: impl Into<String>,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    Ok(match denom {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: recipient.into(),
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount,
+            }],
+        }
+        .into(),
+        Denom::Cw20(address) => WasmMsg::Execute {
+            contract_addr: address.to_string(),
+            msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                recipient: recipient.into(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+/// Creates a message returning the governance tokens held by the
+/// campaign that will never be swapped for funding tokens to the
+/// DAO. `minting` is the number of funding tokens being minted in
+/// the same transaction. Returns `None` if there are no such tokens.
+fn return_gov_tokens(
+    deps: Deps,
+    env: &Env,
+    state: &State,
+    minting: Uint128,
+) -> Result<Option<WasmMsg>, ContractError> {
+    let gov_addr = GOV_TOKEN_ADDR.load(deps.storage)?;
+    let gov_balance: cw20::BalanceResponse = deps.querier.query_wasm_smart(
+        gov_addr.clone(),
+        &cw20::Cw20QueryMsg::Balance {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+
+    let unsold = match state.status {
+        // Funding tokens may be swapped 1:1 for governance tokens so
+        // keep enough around to cover every funding token issued.
+        Status::Funded { .. } => {
+            let funding_token_addr = FUNDING_TOKEN_ADDR.load(deps.storage)?;
+            let funding_token_info: cw20::TokenInfoResponse = deps
+                .querier
+                .query_wasm_smart(funding_token_addr, &cw20::Cw20QueryMsg::TokenInfo {})?;
+            gov_balance
+                .balance
+                .checked_sub(funding_token_info.total_supply.checked_add(minting)?)
+                .map_err(StdError::from)?
+        }
+        _ => gov_balance.balance,
+    };
+    if unsold.is_zero() {
+        return Ok(None);
+    }
+
+    let treasury = state
+        .dao_type
+        .adapter()
+        .treasury(&deps.querier, &state.dao_addr)?;
+    Ok(Some(WasmMsg::Execute {
+        contract_addr: gov_addr.to_string(),
+        msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+            recipient: treasury.to_string(),
+            amount: unsold,
+        })?,
+        funds: vec![],
+    }))
 }
 
 pub fn execute_update_campaign(
@@ -144,96 +461,571 @@ pub fn execute_update_campaign(
         .add_attribute("sender", info.sender))
 }
 
+pub fn execute_update_beneficiaries(
+    deps: DepsMut,
+    sender: Addr,
+    beneficiaries: Vec<BeneficiaryMsg>,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if sender != state.dao_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    state.beneficiaries = validate_beneficiaries(deps.as_ref(), beneficiaries)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "update_beneficiaries")
+        .add_attribute("sender", sender))
+}
+
+/// Validates beneficiary addresses, that each has a weight and
+/// appears once, and that their weights sum without overflowing.
+fn validate_beneficiaries(
+    deps: Deps,
+    beneficiaries: Vec<BeneficiaryMsg>,
+) -> Result<Vec<Beneficiary>, ContractError> {
+    let mut validated: Vec<Beneficiary> = Vec::with_capacity(beneficiaries.len());
+    let mut total_weight: u64 = 0;
+    for BeneficiaryMsg { address, weight } in beneficiaries {
+        let address = deps.api.addr_validate(&address)?;
+        if weight == 0 {
+            return Err(ContractError::InvalidBeneficiaries(format!(
+                "({}) has a weight of zero",
+                address
+            )));
+        }
+        if validated
+            .iter()
+            .any(|beneficiary| beneficiary.address == address)
+        {
+            return Err(ContractError::InvalidBeneficiaries(format!(
+                "({}) is listed more than once",
+                address
+            )));
+        }
+        total_weight = total_weight.checked_add(weight).ok_or_else(|| {
+            ContractError::InvalidBeneficiaries("total weight overflows".to_string())
+        })?;
+        validated.push(Beneficiary { address, weight });
+    }
+    Ok(validated)
+}
+
+/// Splits `amount` between the campaign's beneficiaries by
+/// weight. Rounding dust goes to the last beneficiary so the full
+/// amount is always paid out.
+fn split_payout(deps: Deps, state: &State, amount: Uint128) -> Result<Vec<Payout>, ContractError> {
+    if state.beneficiaries.is_empty() {
+        let treasury = state
+            .dao_type
+            .adapter()
+            .treasury(&deps.querier, &state.dao_addr)?;
+        return Ok(vec![Payout {
+            address: treasury,
+            amount,
+        }]);
+    }
+
+    let total_weight = state
+        .beneficiaries
+        .iter()
+        .try_fold(0u64, |total, beneficiary| {
+            total.checked_add(beneficiary.weight)
+        })
+        .ok_or(ContractError::Overflow {})?;
+    let mut remaining = amount;
+    let mut payouts = state
+        .beneficiaries
+        .iter()
+        .map(|beneficiary| {
+            let share = mul_ratio(amount, beneficiary.weight, total_weight)?;
+            remaining = remaining.checked_sub(share)?;
+            Ok(Payout {
+                address: beneficiary.address.clone(),
+                amount: share,
+            })
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    if let Some(last) = payouts.last_mut() {
+        last.amount = last.amount.checked_add(remaining)?;
+    }
+    Ok(payouts)
+}
+
+/// Creates messages paying `amount` of the funds raised to the DAO's
+/// beneficiaries less the DAO Up! fee.
+fn release_funds(
+    deps: Deps,
+    state: &State,
+    amount: Uint128,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let fee = mul_decimal(amount, state.fee)?;
+
+    let mut messages = split_payout(deps, state, amount - fee)?
+        .into_iter()
+        .filter(|payout| !payout.amount.is_zero())
+        .map(|payout| transfer_funds(&state.funding_denom, payout.address, payout.amount))
+        .collect::<StdResult<Vec<_>>>()?;
+    if !fee.is_zero() {
+        messages.push(transfer_funds(
+            &state.funding_denom,
+            &state.fee_receiver,
+            fee,
+        )?);
+    }
+    Ok(messages)
+}
+
 pub fn execute_close(deps: DepsMut, env: Env, sender: Addr) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
     if sender != state.dao_addr {
         return Err(ContractError::Unauthorized {});
     }
-    let (token_price, initial_gov_token_balance) = match state.status {
+    let return_msg = cancel(deps, &env, &mut state)?;
+    Ok(Response::default()
+        .add_attribute("action", "close")
+        .add_attribute("sender", sender)
+        .add_messages(return_msg))
+}
+
+pub fn execute_abort(deps: DepsMut, env: Env, sender: Addr) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if sender != state.dao_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !matches!(state.status, Status::Pending {} | Status::Uninstantiated {}) {
+        return Err(ContractError::InvalidAbort {});
+    }
+
+    // Return governance tokens sent to the campaign by mistake.
+    let return_msg = return_gov_tokens(deps.as_ref(), &env, &state, Uint128::zero())?;
+
+    state.status = Status::Aborted {};
+    STATE.save(deps.storage, &state)?;
+    Ok(Response::default()
+        .add_attribute("action", "abort")
+        .add_attribute("sender", sender)
+        .add_messages(return_msg))
+}
+
+/// Cancels an open campaign and creates a message returning its
+/// governance tokens to the DAO.
+fn cancel(deps: DepsMut, env: &Env, state: &mut State) -> Result<Option<WasmMsg>, ContractError> {
+    let initial_gov_token_balance = match current_status(state, &env.block) {
         Status::Open {
-            token_price,
             initial_gov_token_balance,
-        } => (token_price, initial_gov_token_balance),
+        } => initial_gov_token_balance,
         _ => return Err(ContractError::InvalidClose {}),
     };
 
     // Return the governance tokens to the DAO.
-    let dao_addr = state.dao_addr.clone();
-    let dao_config: cw3_dao::query::ConfigResponse = deps
-        .querier
-        .query_wasm_smart(dao_addr.clone(), &cw3_dao::msg::QueryMsg::GetConfig {})?;
-    let gov_addr = dao_config.gov_token;
+    let return_msg = return_gov_tokens(deps.as_ref(), env, state, Uint128::zero())?;
+
+    state.status = Status::Cancelled {
+        initial_gov_token_balance,
+    };
+    STATE.save(deps.storage, state)?;
+    Ok(return_msg)
+}
+
+/// Lets the DAO opt in to a fee reduction made by the fee manager
+/// after the campaign was created. Fee increases never apply to
+/// existing campaigns.
+pub fn execute_accept_fee_reduction(
+    deps: DepsMut,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if sender != state.dao_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let fee_manager_config = get_fee_manager_config(deps.as_ref(), &state.fee_manager_addr)?;
+    if fee_manager_config.fee >= state.fee {
+        return Err(ContractError::NoFeeReduction { fee: state.fee });
+    }
+    state.fee = fee_manager_config.fee;
+    state.fee_receiver = fee_manager_config.fee_receiver;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "accept_fee_reduction")
+        .add_attribute("fee", state.fee.to_string())
+        .add_attribute("fee_receiver", state.fee_receiver))
+}
+
+pub fn execute_finalize(deps: DepsMut, env: Env, sender: Addr) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if !matches!(state.status, Status::Open { .. }) {
+        return Err(ContractError::InvalidFinalize {});
+    }
+
+    // Anyone may finalize a campaign once its deadline has
+    // passed. Before that the DAO may end the campaign early once it
+    // has met its minimum goal.
+    let dao_finalize = sender == state.dao_addr && state.funds_raised.amount >= state.min_goal;
+    if !deadline_passed(&state, &env.block) && !dao_finalize {
+        return Err(ContractError::InvalidFinalize {});
+    }
+
+    let status = resolve_status(&state);
+    set_status(&mut state, status, &env.block);
+    STATE.save(deps.storage, &state)?;
+
+    // Return governance tokens that will not be swapped to the DAO.
+    let return_msg = return_gov_tokens(deps.as_ref(), &env, &state, Uint128::zero())?;
+
+    let status = match state.status {
+        Status::Funded { .. } => "funded",
+        _ => "expired",
+    };
+    Ok(Response::default()
+        .add_attribute("action", "finalize")
+        .add_attribute("sender", sender)
+        .add_attribute("status", status)
+        .add_messages(return_msg))
+}
+
+pub fn execute_settle(deps: DepsMut, env: Env, sender: Addr) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if sender != state.dao_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+    let invalid = |reason: &str| Err(ContractError::InvalidSettle(reason.to_string()));
+    if !matches!(state.status, Status::Funded { .. }) {
+        return invalid("only funded campaigns can be settled");
+    }
+    if let Some(config) = &state.milestones {
+        let progress = MILESTONE_PROGRESS
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        if progress.claim.is_some() {
+            return invalid("a milestone claim is pending");
+        }
+        if progress.vetoed.is_none() && (progress.completed as usize) < config.milestones.len() {
+            return invalid("milestones have not all been completed");
+        }
+    }
+
+    // Funding tokens that have not been exchanged forfeit their
+    // governance tokens once the grace period passes.
+    let grace_passed = matches!(
+        state.funded_at,
+        Some(funded_at) if env.block.time >= funded_at.plus_seconds(SETTLEMENT_GRACE_PERIOD)
+    );
+    if !grace_passed && !outstanding_funding_tokens(deps.as_ref(), &env)?.is_zero() {
+        return invalid("funding tokens are outstanding and the grace period has not passed");
+    }
+
+    // Governance tokens that have not been claimed from vesting
+    // schedules still belong to their backers.
+    let vesting = VESTING
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, vesting)| vesting.total - vesting.claimed))
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .sum::<Uint128>();
+    let gov_addr = GOV_TOKEN_ADDR.load(deps.storage)?;
     let gov_balance: cw20::BalanceResponse = deps.querier.query_wasm_smart(
         gov_addr.clone(),
         &cw20::Cw20QueryMsg::Balance {
             address: env.contract.address.to_string(),
         },
     )?;
+    let gov_residual = gov_balance.balance.saturating_sub(vesting);
 
-    let return_msg = WasmMsg::Execute {
-        contract_addr: gov_addr.to_string(),
-        msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
-            recipient: dao_addr.to_string(),
-            amount: gov_balance.balance,
-        })?,
-        funds: vec![],
+    let funds_residual = match &state.funding_denom {
+        Denom::Native(denom) => {
+            deps.querier
+                .query_balance(&env.contract.address, denom)?
+                .amount
+        }
+        Denom::Cw20(address) => {
+            let balance: cw20::BalanceResponse = deps.querier.query_wasm_smart(
+                address,
+                &cw20::Cw20QueryMsg::Balance {
+                    address: env.contract.address.to_string(),
+                },
+            )?;
+            balance.balance
+        }
     };
 
-    state.status = Status::Cancelled {
-        token_price,
-        initial_gov_token_balance,
-    };
+    let mut messages = vec![];
+    if !gov_residual.is_zero() {
+        let treasury = state
+            .dao_type
+            .adapter()
+            .treasury(&deps.querier, &state.dao_addr)?;
+        messages.push(CosmosMsg::from(WasmMsg::Execute {
+            contract_addr: gov_addr.to_string(),
+            msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                recipient: treasury.to_string(),
+                amount: gov_residual,
+            })?,
+            funds: vec![],
+        }));
+    }
+    if !funds_residual.is_zero() {
+        messages.extend(release_funds(deps.as_ref(), &state, funds_residual)?);
+    }
+
+    state.status = Status::Settled {};
     STATE.save(deps.storage, &state)?;
+
     Ok(Response::default()
-        .add_attribute("action", "close")
+        .add_attribute("action", "settle")
         .add_attribute("sender", sender)
-        .add_message(return_msg))
+        .add_attribute("gov_tokens_returned", gov_residual)
+        .add_attribute("funds_released", funds_residual)
+        .add_messages(messages))
 }
 
-pub fn execute_fund(
+pub fn execute_process_refunds(
     deps: DepsMut,
-    funds: &[Coin],
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+
+    let status = current_status(&state, &env.block);
+    if !matches!(status, Status::Cancelled { .. } | Status::Expired { .. }) {
+        return Err(ContractError::InvalidProcessRefunds {});
+    }
+    if state.unrecorded_contributions {
+        return Err(ContractError::NoBatchRefunds {});
+    }
+    let response = if status != state.status {
+        // The campaign's deadline has passed since it was last
+        // updated. Resolve it and return its governance tokens to the
+        // DAO along with the refunds.
+        set_status(&mut state, status, &env.block);
+        Response::default().add_messages(return_gov_tokens(
+            deps.as_ref(),
+            &env,
+            &state,
+            Uint128::zero(),
+        )?)
+    } else {
+        Response::default()
+    };
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let cursor = REFUND_CURSOR.may_load(deps.storage)?;
+    let min = cursor.as_ref().map(|addr| Bound::exclusive(addr.as_str()));
+    let contributors = CONTRIBUTIONS
+        .range(deps.storage, min, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut messages = vec![];
+    let mut refunded = 0;
+    for (address, mut contribution) in contributors.iter().cloned() {
+        // Contributors are refunded from the ledger whether or not
+        // they still hold their funding tokens. The tokens are left
+        // unburned and, as refunds are only paid for tokens the
+        // ledger says the sender holds, can no longer be refunded.
+        let native_owed = contribution.deposited.saturating_sub(contribution.refunded);
+        if native_owed.is_zero() {
+            continue;
+        }
+
+        contribution.refunded = contribution.refunded.checked_add(native_owed)?;
+        contribution.burned = contribution.burned.max(contribution.minted);
+        CONTRIBUTIONS.save(deps.storage, &address, &contribution)?;
+        state.funds_raised.amount = state.funds_raised.amount.checked_sub(native_owed)?;
+
+        messages.push(transfer_funds(&state.funding_denom, &address, native_owed)?);
+        refunded += 1;
+    }
+    STATE.save(deps.storage, &state)?;
+
+    // Start over once every contributor has been visited.
+    let complete = contributors.len() < limit;
+    match contributors.last() {
+        Some((address, _)) if !complete => REFUND_CURSOR.save(deps.storage, address)?,
+        _ => REFUND_CURSOR.remove(deps.storage),
+    }
+
+    Ok(response
+        .add_attribute("action", "process_refunds")
+        .add_attribute("refunded", refunded.to_string())
+        .add_attribute("complete", complete.to_string())
+        .add_messages(messages))
+}
+
+pub fn execute_update_presale_merkle_root(
+    deps: DepsMut,
+    env: Env,
     sender: Addr,
+    merkle_root: String,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
+    if sender != state.dao_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let presale = state.presale.as_mut().ok_or(ContractError::NoPresale {})?;
+    if presale.ends.is_expired(&env.block) {
+        return Err(ContractError::PresaleEnded {});
+    }
+    validate_merkle_root(&merkle_root)?;
+    presale.merkle_root = merkle_root.clone();
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "update_presale_merkle_root")
+        .add_attribute("merkle_root", merkle_root))
+}
 
-    let (token_price, initial_gov_token_balance) = match state.status {
+/// Computes the outcome of `sender` contributing `amount` to the
+/// campaign. Only as much as is needed to reach the funding goal is
+/// accepted and the rest is refunded. During the campaign's presale
+/// `presale` must prove that the sender is allowlisted.
+pub fn simulate_fund(
+    deps: Deps,
+    state: &State,
+    block: &BlockInfo,
+    sender: &Addr,
+    amount: Uint128,
+    presale: Option<&PresaleProof>,
+) -> Result<SimulateFundResponse, ContractError> {
+    let initial_gov_token_balance = match current_status(state, block) {
         Status::Open {
-            token_price,
             initial_gov_token_balance,
-        } => Ok((token_price, initial_gov_token_balance)),
+        } => Ok(initial_gov_token_balance),
+        Status::Expired { .. } => Err(ContractError::Expired {}),
         _ => Err(ContractError::NotOpen {}),
     }?;
 
-    let payment = funds
-        .iter()
-        .filter(|coin| coin.denom == state.funding_goal.denom)
-        .fold(Uint128::zero(), |accum, coin| coin.amount + accum);
+    let remaining = state.funding_goal.amount - state.funds_raised.amount;
+    let (accepted, refunded) = if amount > remaining {
+        (remaining, amount - remaining)
+    } else {
+        (amount, Uint128::zero())
+    };
 
-    // Don't allow funding over the funding goal.
-    if state.funds_raised.amount + payment > state.funding_goal.amount {
-        return Err(ContractError::FundingOverflow {});
+    // The contribution completing the campaign may be smaller than
+    // the minimum if that is all that is left to raise.
+    if let Some(min) = state.min_contribution {
+        if amount < min.min(remaining) {
+            return Err(ContractError::ContributionTooSmall { min });
+        }
     }
 
-    // Update the amount raised.
-    state.funds_raised.amount += payment;
-    // If we've met the funding goal set the state to complete.
-    if state.funds_raised.amount == state.funding_goal.amount {
-        state.status = Status::Funded {
-            token_price,
-            initial_gov_token_balance,
-        };
+    let contribution = CONTRIBUTIONS
+        .may_load(deps.storage, sender)?
+        .unwrap_or_default();
+    let contributed = contribution.deposited.saturating_sub(contribution.refunded);
+
+    if let Some(active) = state
+        .presale
+        .as_ref()
+        .filter(|active| !active.ends.is_expired(block))
+    {
+        let PresaleProof { allocation, proof } = presale.ok_or(ContractError::PresaleOnly {})?;
+        verify_proof(&active.merkle_root, sender, *allocation, proof)?;
+        if contributed.checked_add(accepted)? > *allocation {
+            return Err(ContractError::PresaleAllocationExceeded {
+                allocation: *allocation,
+                remaining: allocation.saturating_sub(contributed),
+            });
+        }
     }
-    STATE.save(deps.storage, &state)?;
 
-    let funding_tokens_owed = token_price * payment;
+    if let Some(max) = state.max_contribution_per_address {
+        if contributed.checked_add(accepted)? > max {
+            return Err(ContractError::ContributionLimitExceeded {
+                max,
+                remaining: max.saturating_sub(contributed),
+            });
+        }
+    }
+
+    let base = state.pricing.tokens_for(
+        state.funding_goal.amount,
+        sale_reserve(&state.bonus_windows, initial_gov_token_balance)?,
+        state.funds_raised.amount,
+        accepted,
+    )?;
+    let bonus = match state.opened_at {
+        Some(opened_at) => {
+            mul_decimal(base, bonus_at(&state.bonus_windows, opened_at, block.time))?
+        }
+        None => Uint128::zero(),
+    };
+    let minted = base.checked_add(bonus)?;
 
     // Reject transactions that would cause no tokens to be
     // issued. This could happen if the smallest token's price is
     // greater than the contribution.
-    if funding_tokens_owed.is_zero() {
-        return Err(ContractError::SmallContribution { token_price });
+    if minted.is_zero() {
+        return Err(ContractError::SmallContribution {});
+    }
+
+    Ok(SimulateFundResponse {
+        accepted,
+        refunded,
+        minted,
+        bonus,
+    })
+}
+
+pub fn execute_fund(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    funds: Balance,
+    presale: Option<PresaleProof>,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+
+    let payment = match (&state.funding_denom, funds) {
+        (Denom::Native(denom), Balance::Native(funds)) => funds
+            .0
+            .iter()
+            .filter(|coin| &coin.denom == denom)
+            .try_fold(Uint128::zero(), |accum, coin| {
+                accum.checked_add(coin.amount)
+            })?,
+        (Denom::Cw20(address), Balance::Cw20(coin)) if &coin.address == address => coin.amount,
+        _ => return Err(ContractError::InvalidFundingAsset {}),
+    };
+
+    let SimulateFundResponse {
+        accepted: payment,
+        refunded: excess,
+        minted: funding_tokens_owed,
+        ..
+    } = simulate_fund(
+        deps.as_ref(),
+        &state,
+        &env.block,
+        &sender,
+        payment,
+        presale.as_ref(),
+    )?;
+
+    // Update the amount raised.
+    state.funds_raised.amount += payment;
+    // If we've met the funding goal set the state to complete.
+    if state.funds_raised.amount == state.funding_goal.amount {
+        let status = resolve_status(&state);
+        set_status(&mut state, status, &env.block);
     }
+    STATE.save(deps.storage, &state)?;
+
+    CONTRIBUTIONS.update(
+        deps.storage,
+        &sender,
+        |contribution| -> Result<_, ContractError> {
+            let mut contribution = contribution.unwrap_or_default();
+            contribution.deposited = contribution.deposited.checked_add(payment)?;
+            contribution.minted = contribution.minted.checked_add(funding_tokens_owed)?;
+            Ok(contribution)
+        },
+    )?;
 
     let funding_token_address = FUNDING_TOKEN_ADDR.load(deps.storage)?;
     let mint_msg = WasmMsg::Execute {
@@ -245,24 +1037,68 @@ pub fn execute_fund(
         funds: vec![],
     };
 
-    Ok(Response::default()
+    let response = Response::default()
         .add_attribute("action", "fund")
-        .add_attribute("sender", sender)
+        .add_attribute("sender", &sender)
         .add_attribute("amount", payment)
-        .add_message(mint_msg))
+        .add_message(mint_msg);
+
+    // Return governance tokens left over from the pricing curve and
+    // bonuses once the campaign is funded.
+    let response = if let Status::Funded { .. } = state.status {
+        response.add_messages(return_gov_tokens(
+            deps.as_ref(),
+            &env,
+            &state,
+            funding_tokens_owed,
+        )?)
+    } else {
+        response
+    };
+
+    Ok(if excess.is_zero() {
+        response
+    } else {
+        response
+            .add_attribute("refunded", excess)
+            .add_message(transfer_funds(&state.funding_denom, &sender, excess)?)
+    })
 }
 
 pub fn execute_receive(
     deps: DepsMut,
+    env: Env,
     msg: Cw20ReceiveMsg,
     sender: Addr,
 ) -> Result<Response, ContractError> {
     let gov_token_addr = GOV_TOKEN_ADDR.load(deps.storage)?;
     let funding_token_addr = FUNDING_TOKEN_ADDR.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
     if sender == gov_token_addr {
-        execute_receive_gov_tokens(deps, msg)
+        execute_receive_gov_tokens(deps, env, msg)
     } else if sender == funding_token_addr {
-        execute_receive_funding_tokens(deps, msg, funding_token_addr)
+        execute_receive_funding_tokens(deps, env, msg, funding_token_addr)
+    } else if state.funding_denom == Denom::Cw20(sender.clone()) {
+        // Contributions to campaigns raising funds in a cw20
+        // token. Sends without a recognized message are plain
+        // contributions.
+        let contributor = deps.api.addr_validate(&msg.sender)?;
+        let presale = match from_binary(&msg.msg) {
+            Ok(ReceiveMsg::FundPresale { allocation, proof }) => {
+                Some(PresaleProof { allocation, proof })
+            }
+            Ok(ReceiveMsg::Vote {}) | Err(_) => None,
+        };
+        execute_fund(
+            deps,
+            env,
+            contributor,
+            Balance::Cw20(Cw20CoinVerified {
+                address: sender,
+                amount: msg.amount,
+            }),
+            presale,
+        )
     } else {
         Err(ContractError::Unauthorized {})
     }
@@ -270,22 +1106,45 @@ pub fn execute_receive(
 
 pub fn execute_receive_gov_tokens(
     deps: DepsMut,
+    env: Env,
     msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
     match state.status {
         Status::Pending {} => {
-            let token_price = Decimal::from_ratio(msg.amount, state.funding_goal.amount);
-            // Not strictly needed as fund transactions will fail the
-            // the token price is zero but slightly better UX.
-            if token_price.is_zero() {
+            // Don't open campaigns that would immediately expire.
+            if deadline_passed(&state, &env.block) {
+                return Err(ContractError::Expired {});
+            }
+
+            // Not strictly needed as fund transactions will fail if
+            // no governance tokens are for sale but slightly better
+            // UX.
+            if msg.amount.is_zero() {
                 return Err(ContractError::InvalidGovTokenAmount {});
             }
 
+            // The DAO must send enough tokens to cover the pricing
+            // curve up to the funding goal along with the largest
+            // bonus on all of them.
+            let reserve = sale_reserve(&state.bonus_windows, msg.amount)?;
+            let sold = state.pricing.tokens_for(
+                state.funding_goal.amount,
+                reserve,
+                Uint128::zero(),
+                state.funding_goal.amount,
+            )?;
+            if sold > reserve {
+                return Err(ContractError::InsufficientGovTokens {
+                    required: sold
+                        .checked_add(mul_decimal(sold, max_bonus(&state.bonus_windows))?)?,
+                });
+            }
+
             state.status = Status::Open {
-                token_price,
                 initial_gov_token_balance: msg.amount,
             };
+            state.opened_at = Some(env.block.time);
             STATE.save(deps.storage, &state)?;
             Ok(Response::default()
                 .add_attribute("action", "fund_gov_tokens")
@@ -297,31 +1156,110 @@ pub fn execute_receive_gov_tokens(
 
 pub fn execute_receive_funding_tokens(
     deps: DepsMut,
+    env: Env,
     msg: Cw20ReceiveMsg,
     funding_token_addr: Addr,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
+
+    let status = current_status(&state, &env.block);
+    let response = if status != state.status {
+        // The campaign's deadline has passed since it was last
+        // updated. Resolve it and return unneeded governance tokens
+        // to the DAO along with the refund or swap.
+        set_status(&mut state, status, &env.block);
+        STATE.save(deps.storage, &state)?;
+        Response::default().add_messages(return_gov_tokens(
+            deps.as_ref(),
+            &env,
+            &state,
+            Uint128::zero(),
+        )?)
+    } else {
+        Response::default()
+    };
+
+    let sender = deps.api.addr_validate(&msg.sender)?;
+    if let Ok(ReceiveMsg::Vote {}) = from_binary(&msg.msg) {
+        return execute_vote(deps, env, response, state, sender, msg.amount);
+    }
+
     match state.status {
-        Status::Pending {} | Status::Uninstantiated {} => Err(ContractError::NotOpen {}),
-        Status::Open { token_price, .. } | Status::Cancelled { token_price, .. } => {
-            // User is sending tokens back to the contract indicating
-            // that they would like a refund.
-            let sender = deps.api.addr_validate(&msg.sender)?;
+        Status::Pending {} | Status::Uninstantiated {} | Status::Aborted {} => {
+            Err(ContractError::NotOpen {})
+        }
+        Status::Settled {} => Err(ContractError::Settled {}),
+        Status::Funded { .. }
+            if MILESTONE_PROGRESS
+                .may_load(deps.storage)?
+                .unwrap_or_default()
+                .vetoed
+                .is_some() =>
+        {
+            // User is returning tokens for their share of the funds
+            // that were not released before a milestone was vetoed.
+            let SimulateRefundResponse {
+                refunded: native_owed,
+                ..
+            } = simulate_refund(deps.as_ref(), &state, &env.block, &sender, msg.amount)?;
+
+            MILESTONE_PROGRESS.update(deps.storage, |mut progress| -> StdResult<_> {
+                if let Some(vetoed) = progress.vetoed.as_mut() {
+                    vetoed.unreleased -= native_owed;
+                    vetoed.outstanding -= msg.amount;
+                }
+                Ok(progress)
+            })?;
+            CONTRIBUTIONS.update(deps.storage, &sender, |contribution| -> StdResult<_> {
+                let mut contribution = contribution.unwrap_or_default();
+                contribution.refunded += native_owed;
+                contribution.burned += msg.amount;
+                Ok(contribution)
+            })?;
 
-            // Token price is in tokens / native. `tokens * 1 /
-            // (tokens / native)` = native owed.
-            let native_owed = msg.amount * token_price.inv().unwrap();
-            if native_owed.is_zero() {
-                return Err(ContractError::SmallRefund { token_price });
-            }
+            // The governance tokens backing the returned tokens will
+            // never be swapped so return them to the DAO.
+            let treasury = state
+                .dao_type
+                .adapter()
+                .treasury(&deps.querier, &state.dao_addr)?;
+            let gov_addr = GOV_TOKEN_ADDR.load(deps.storage)?;
+            let gov_return = WasmMsg::Execute {
+                contract_addr: gov_addr.to_string(),
+                msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                    recipient: treasury.to_string(),
+                    amount: msg.amount,
+                })?,
+                funds: vec![],
+            };
+            let burn_msg = WasmMsg::Execute {
+                contract_addr: funding_token_addr.to_string(),
+                msg: to_binary(&cw20::Cw20ExecuteMsg::Burn { amount: msg.amount })?,
+                funds: vec![],
+            };
 
-            let bank_msg = BankMsg::Send {
-                to_address: sender.to_string(),
-                amount: vec![Coin {
-                    denom: state.funding_goal.denom.clone(),
-                    amount: native_owed,
-                }],
+            let response = if native_owed.is_zero() {
+                response
+            } else {
+                response.add_message(transfer_funds(&state.funding_denom, &sender, native_owed)?)
             };
+            Ok(response
+                .add_attribute("action", "refund")
+                .add_attribute("sender", &sender)
+                .add_attribute("tokens_returned", msg.amount)
+                .add_attribute("native_returned", native_owed)
+                .add_message(burn_msg)
+                .add_message(gov_return))
+        }
+        Status::Open { .. } | Status::Cancelled { .. } | Status::Expired { .. } => {
+            // User is sending tokens back to the contract indicating
+            // that they would like a refund.
+            let SimulateRefundResponse {
+                refunded: native_owed,
+                ..
+            } = simulate_refund(deps.as_ref(), &state, &env.block, &sender, msg.amount)?;
+
+            let refund_msg = transfer_funds(&state.funding_denom, &sender, native_owed)?;
 
             // Burn the returned tokens.
             let burn_msg = WasmMsg::Execute {
@@ -331,96 +1269,588 @@ pub fn execute_receive_funding_tokens(
             };
 
             // Update the funding goal counter.
-            state.funds_raised.amount -= native_owed;
+            state.funds_raised.amount = state.funds_raised.amount.checked_sub(native_owed)?;
             STATE.save(deps.storage, &state)?;
 
-            Ok(Response::default()
+            CONTRIBUTIONS.update(deps.storage, &sender, |contribution| -> StdResult<_> {
+                let mut contribution = contribution.unwrap_or_default();
+                contribution.refunded += native_owed;
+                contribution.burned += msg.amount;
+                Ok(contribution)
+            })?;
+
+            Ok(response
                 .add_attribute("action", "refund")
                 .add_attribute("sender", sender)
                 .add_attribute("tokens_returned", msg.amount)
                 .add_attribute("native_returned", native_owed)
-                .add_message(bank_msg)
+                .add_message(refund_msg)
                 .add_message(burn_msg))
         }
-        Status::Funded { token_price, .. } => {
+        Status::Funded { .. } => {
             // User is sending tokens back to the contract indicating
-            // that they would like staked governance tokens.
-            let sender = deps.api.addr_validate(&msg.sender)?;
-
-            // Some math here. TL;DR there will always be a 1:1
-            // relationship between funding tokens and gov tokens.
-            //
-            // token_price = gov_tokens / funding_goal
-            // => funding_goal * token_price = gov_tokens
-            //
-            // Once funding goal has been met we will have issued
-            // `gov_tokens` tokens. This is a slightly idealized
-            // example because in real life there is rounding which we
-            // always round down but insofar as what we issue this is
-            // correct.
-            let gov_owed = msg.amount;
-
-            let state = STATE.load(deps.storage)?;
-            let dao_config: cw3_dao::query::ConfigResponse = deps.querier.query_wasm_smart(
-                state.dao_addr.clone(),
-                &cw3_dao::msg::QueryMsg::GetConfig {},
-            )?;
-            let gov_addr = dao_config.gov_token;
-            let dao_addr = state.dao_addr.to_string();
+            // that they would like governance tokens. These are sent
+            // unstaked as the DAO's staking contract can only stake
+            // on behalf of the address that sends it tokens.
+
+            let SimulateSwapResponse {
+                gov_tokens: gov_owed,
+                payouts,
+                fee: fee_amount,
+                ..
+            } = simulate_swap(deps.as_ref(), &state, &env.block, msg.amount)?;
+
+            CONTRIBUTIONS.update(deps.storage, &sender, |contribution| -> StdResult<_> {
+                let mut contribution = contribution.unwrap_or_default();
+                contribution.swapped += gov_owed;
+                Ok(contribution)
+            })?;
 
-            // Transfer gov tokens to the sender.
-            let token_transfer = WasmMsg::Execute {
-                contract_addr: gov_addr.to_string(),
-                msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
-                    recipient: sender.to_string(),
-                    amount: gov_owed,
-                })?,
-                funds: vec![],
+            let response = if state.vesting.is_some() {
+                // Hold the gov tokens until they vest.
+                VESTING.update(deps.storage, &sender, |vesting| -> StdResult<_> {
+                    let mut vesting = vesting.unwrap_or_default();
+                    vesting.total += gov_owed;
+                    Ok(vesting)
+                })?;
+                response.add_attribute("vesting", gov_owed)
+            } else {
+                let gov_addr = GOV_TOKEN_ADDR.load(deps.storage)?;
+
+                // Transfer gov tokens to the sender.
+                let token_transfer = WasmMsg::Execute {
+                    contract_addr: gov_addr.to_string(),
+                    msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                        recipient: sender.to_string(),
+                        amount: gov_owed,
+                    })?,
+                    funds: vec![],
+                };
+                response.add_message(token_transfer)
             };
 
-            // Get fee manager information.
-            let fee_manager_config = get_fee_manager_config(&deps, &state.fee_manager_addr)?;
-
-            let native_to_transfer = msg.amount * token_price.inv().unwrap();
-            let fee_amount = native_to_transfer * fee_manager_config.fee;
-            let dao_amount = native_to_transfer - fee_amount;
-
-            // Transfer a proportional amount of funds to the DAO.
-            let dao_transfer = BankMsg::Send {
-                to_address: dao_addr,
-                amount: vec![Coin {
-                    denom: state.funding_goal.denom.clone(),
-                    amount: dao_amount,
-                }],
-            };
+            // Transfer a proportional amount of funds to the DAO's
+            // beneficiaries. Campaigns releasing funds by milestone
+            // hold them instead.
+            let dao_transfers = payouts
+                .into_iter()
+                .filter(|payout| !payout.amount.is_zero())
+                .map(|payout| transfer_funds(&state.funding_denom, payout.address, payout.amount))
+                .collect::<StdResult<Vec<_>>>()?;
 
             // If fee present, transfer fee.
-            let response = if fee_manager_config.fee > Decimal::zero() {
+            let response = if !fee_amount.is_zero() {
                 // Transfer fee to the fee account.
-                let fee_transfer = BankMsg::Send {
-                    to_address: fee_manager_config.fee_receiver.to_string(),
-                    amount: vec![Coin {
-                        denom: state.funding_goal.denom,
-                        amount: fee_amount,
-                    }],
-                };
+                let fee_transfer =
+                    transfer_funds(&state.funding_denom, &state.fee_receiver, fee_amount)?;
 
-                Response::default().add_message(fee_transfer)
+                response.add_message(fee_transfer)
             } else {
-                Response::default()
+                response
             };
 
             Ok(response
                 .add_attribute("action", "swap_for_gov")
                 .add_attribute("sender", sender)
-                .add_message(token_transfer)
-                .add_message(dao_transfer))
+                .add_messages(dao_transfers))
+        }
+    }
+}
+
+/// Gets the number of funding tokens that may vote in polls. Funding
+/// tokens held by the campaign that are not locked in votes have been
+/// exchanged for governance tokens and may not vote.
+fn outstanding_funding_tokens(deps: Deps, env: &Env) -> StdResult<Uint128> {
+    let funding_token_addr = FUNDING_TOKEN_ADDR.load(deps.storage)?;
+    let funding_token_info: cw20::TokenInfoResponse = deps.querier.query_wasm_smart(
+        funding_token_addr.clone(),
+        &cw20::Cw20QueryMsg::TokenInfo {},
+    )?;
+    let held: cw20::BalanceResponse = deps.querier.query_wasm_smart(
+        funding_token_addr,
+        &cw20::Cw20QueryMsg::Balance {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+    let locked = VOTES_LOCKED.may_load(deps.storage)?.unwrap_or_default();
+    Ok(funding_token_info.total_supply - held.balance + locked)
+}
+
+/// Locks `tokens` funding tokens sent by `sender` in favour of the
+/// open poll and carries the poll out if it passes.
+fn execute_vote(
+    deps: DepsMut,
+    env: Env,
+    response: Response,
+    state: State,
+    sender: Addr,
+    tokens: Uint128,
+) -> Result<Response, ContractError> {
+    let mut poll = POLL
+        .may_load(deps.storage)?
+        .filter(|poll| poll.is_open(&env.block))
+        .ok_or(ContractError::NoPoll {})?;
+    // Cancellation polls lapse once the campaign stops accepting
+    // funds.
+    if poll.kind == (PollKind::Cancel {}) && !matches!(state.status, Status::Open { .. }) {
+        return Err(ContractError::NotOpen {});
+    }
+
+    let locked = match VOTES.may_load(deps.storage, &sender)? {
+        Some(vote) if vote.poll == poll.id => vote.tokens + tokens,
+        Some(_) => return Err(ContractError::StaleVote {}),
+        None => tokens,
+    };
+    VOTES.save(
+        deps.storage,
+        &sender,
+        &LockedVote {
+            poll: poll.id,
+            tokens: locked,
+        },
+    )?;
+    let total_locked = VOTES_LOCKED.may_load(deps.storage)?.unwrap_or_default();
+    VOTES_LOCKED.save(deps.storage, &(total_locked + tokens))?;
+    poll.votes += tokens;
+
+    let outstanding = outstanding_funding_tokens(deps.as_ref(), &env)?;
+    poll.passed = poll.reached_threshold(outstanding);
+    POLL.save(deps.storage, &poll)?;
+
+    let response = response
+        .add_attribute("action", "vote")
+        .add_attribute("sender", sender)
+        .add_attribute("poll", poll.id.to_string())
+        .add_attribute("tokens", tokens)
+        .add_attribute("passed", poll.passed.to_string());
+    if !poll.passed {
+        return Ok(response);
+    }
+
+    Ok(match poll.kind {
+        PollKind::VetoMilestone { .. } => {
+            response.add_messages(veto_milestone(deps, &state, outstanding)?)
+        }
+        PollKind::Cancel {} => {
+            let mut state = state;
+            response
+                .add_attribute("status", "cancelled")
+                .add_messages(cancel(deps, &env, &mut state)?)
+        }
+    })
+}
+
+/// Gets the ID the next poll opened should have.
+fn next_poll_id(deps: Deps) -> StdResult<u64> {
+    Ok(POLL
+        .may_load(deps.storage)?
+        .map(|poll| poll.id + 1)
+        .unwrap_or(1))
+}
+
+pub fn execute_open_cancellation_poll(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let config = state
+        .cancellation
+        .as_ref()
+        .ok_or(ContractError::NoCancellation {})?;
+    if !matches!(current_status(&state, &env.block), Status::Open { .. }) {
+        return Err(ContractError::NotOpen {});
+    }
+
+    let funding_token_addr = FUNDING_TOKEN_ADDR.load(deps.storage)?;
+    let balance: cw20::BalanceResponse = deps.querier.query_wasm_smart(
+        funding_token_addr,
+        &cw20::Cw20QueryMsg::Balance {
+            address: sender.to_string(),
+        },
+    )?;
+    if balance.balance.is_zero() {
+        return Err(ContractError::Unauthorized {});
+    }
+    if matches!(POLL.may_load(deps.storage)?, Some(poll) if poll.is_open(&env.block)) {
+        return Err(ContractError::PollOpen {});
+    }
+
+    let poll = Poll {
+        id: next_poll_id(deps.as_ref())?,
+        kind: PollKind::Cancel {},
+        threshold: config.quorum,
+        ends: env.block.time.plus_seconds(config.voting_period),
+        votes: Uint128::zero(),
+        passed: false,
+    };
+    POLL.save(deps.storage, &poll)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "open_cancellation_poll")
+        .add_attribute("sender", sender)
+        .add_attribute("poll", poll.id.to_string()))
+}
+
+/// Cancels the pending milestone claim and stops further claims. The
+/// share of unreleased funds belonging to funding tokens that have
+/// been exchanged for governance tokens is released to the DAO and
+/// the rest is set aside for refunds.
+fn veto_milestone(
+    deps: DepsMut,
+    state: &State,
+    outstanding: Uint128,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut progress = MILESTONE_PROGRESS.load(deps.storage)?;
+    let funding_token_addr = FUNDING_TOKEN_ADDR.load(deps.storage)?;
+    let funding_token_info: cw20::TokenInfoResponse = deps
+        .querier
+        .query_wasm_smart(funding_token_addr, &cw20::Cw20QueryMsg::TokenInfo {})?;
+
+    let unreleased = state.funds_raised.amount - progress.released;
+    let refundable = mul_ratio(unreleased, outstanding, funding_token_info.total_supply)?;
+    let swapped_share = unreleased - refundable;
+
+    progress.claim = None;
+    progress.released += swapped_share;
+    progress.vetoed = Some(VetoRefunds {
+        unreleased: refundable,
+        outstanding,
+    });
+    MILESTONE_PROGRESS.save(deps.storage, &progress)?;
+
+    if swapped_share.is_zero() {
+        Ok(vec![])
+    } else {
+        release_funds(deps.as_ref(), state, swapped_share)
+    }
+}
+
+pub fn execute_claim_milestone(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if sender != state.dao_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+    let config = state
+        .milestones
+        .as_ref()
+        .ok_or(ContractError::NoMilestones {})?;
+    // Campaigns funded by their deadline passing must be finalized
+    // first.
+    if !matches!(state.status, Status::Funded { .. }) {
+        return Err(ContractError::NotFunded {});
+    }
+
+    let mut progress = MILESTONE_PROGRESS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if progress.vetoed.is_some() {
+        return Err(ContractError::MilestonesVetoed {});
+    }
+    if progress.claim.is_some() {
+        return Err(ContractError::MilestoneClaimPending {});
+    }
+    let index = progress.completed as usize;
+    let milestone = config
+        .milestones
+        .get(index)
+        .ok_or(ContractError::MilestonesComplete {})?;
+
+    // The last milestone releases whatever is left so rounding never
+    // strands funds.
+    let amount = if index + 1 == config.milestones.len() {
+        state.funds_raised.amount - progress.released
+    } else {
+        mul_decimal(state.funds_raised.amount, milestone.release)?
+    };
+
+    let poll = Poll {
+        id: next_poll_id(deps.as_ref())?,
+        kind: PollKind::VetoMilestone {
+            milestone: progress.completed,
+        },
+        threshold: config.veto_threshold,
+        ends: env.block.time.plus_seconds(config.challenge_period),
+        votes: Uint128::zero(),
+        passed: false,
+    };
+    progress.claim = Some(MilestoneClaim {
+        milestone: progress.completed,
+        amount,
+        poll: poll.id,
+    });
+    POLL.save(deps.storage, &poll)?;
+    MILESTONE_PROGRESS.save(deps.storage, &progress)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "claim_milestone")
+        .add_attribute("milestone", progress.completed.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("poll", poll.id.to_string()))
+}
+
+pub fn execute_release_milestone(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let mut progress = MILESTONE_PROGRESS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let claim = progress
+        .claim
+        .take()
+        .ok_or(ContractError::NoMilestoneClaim {})?;
+    if POLL.load(deps.storage)?.is_open(&env.block) {
+        return Err(ContractError::ChallengePeriodActive {});
+    }
+
+    progress.completed += 1;
+    progress.released += claim.amount;
+    MILESTONE_PROGRESS.save(deps.storage, &progress)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "release_milestone")
+        .add_attribute("milestone", claim.milestone.to_string())
+        .add_attribute("amount", claim.amount)
+        .add_messages(release_funds(deps.as_ref(), &state, claim.amount)?))
+}
+
+pub fn execute_withdraw_vote(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let vote = VOTES
+        .may_load(deps.storage, &sender)?
+        .ok_or(ContractError::NoVote {})?;
+    if matches!(POLL.may_load(deps.storage)?, Some(poll) if poll.id == vote.poll && poll.is_open(&env.block))
+    {
+        return Err(ContractError::VoteLocked {});
+    }
+
+    VOTES.remove(deps.storage, &sender);
+    let total_locked = VOTES_LOCKED.load(deps.storage)?;
+    VOTES_LOCKED.save(deps.storage, &(total_locked - vote.tokens))?;
+
+    let funding_token_addr = FUNDING_TOKEN_ADDR.load(deps.storage)?;
+    Ok(Response::default()
+        .add_attribute("action", "withdraw_vote")
+        .add_attribute("sender", &sender)
+        .add_attribute("tokens", vote.tokens)
+        .add_message(WasmMsg::Execute {
+            contract_addr: funding_token_addr.to_string(),
+            msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                recipient: sender.to_string(),
+                amount: vote.tokens,
+            })?,
+            funds: vec![],
+        }))
+}
+
+pub fn execute_claim_vested(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let VestingResponse {
+        claimable, claimed, ..
+    } = vesting_of(deps.as_ref(), &state, &env.block, &sender)?;
+    if claimable.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    VESTING.update(deps.storage, &sender, |vesting| -> StdResult<_> {
+        let mut vesting = vesting.unwrap_or_default();
+        vesting.claimed = claimed + claimable;
+        Ok(vesting)
+    })?;
+
+    let gov_addr = GOV_TOKEN_ADDR.load(deps.storage)?;
+    let token_transfer = WasmMsg::Execute {
+        contract_addr: gov_addr.to_string(),
+        msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+            recipient: sender.to_string(),
+            amount: claimable,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::default()
+        .add_attribute("action", "claim_vested")
+        .add_attribute("sender", sender)
+        .add_attribute("amount", claimable)
+        .add_message(token_transfer))
+}
+
+/// Gets the governance tokens vesting for `address` at `block`.
+pub fn vesting_of(
+    deps: Deps,
+    state: &State,
+    block: &BlockInfo,
+    address: &Addr,
+) -> StdResult<VestingResponse> {
+    let Vesting { total, claimed } = VESTING.may_load(deps.storage, address)?.unwrap_or_default();
+    let vested = match (&state.vesting, state.funded_at) {
+        (Some(config), Some(funded_at)) => config.vested(total, funded_at, block.time),
+        _ => Uint128::zero(),
+    };
+    Ok(VestingResponse {
+        total,
+        vested,
+        claimed,
+        claimable: vested - claimed,
+    })
+}
+
+/// Computes the outcome of `sender` returning `tokens` funding
+/// tokens to the campaign for a refund.
+///
+/// Refunds unwind at the average price the sender paid for the
+/// tokens they were issued. Campaigns with fixed pricing also refund
+/// tokens the sender was not issued, for example ones transferred to
+/// them, at the campaign's token price.
+pub fn simulate_refund(
+    deps: Deps,
+    state: &State,
+    block: &BlockInfo,
+    sender: &Addr,
+    tokens: Uint128,
+) -> Result<SimulateRefundResponse, ContractError> {
+    match current_status(state, block) {
+        Status::Pending {} | Status::Uninstantiated {} | Status::Aborted {} => {
+            Err(ContractError::NotOpen {})
+        }
+        Status::Settled {} => Err(ContractError::Settled {}),
+        Status::Open {
+            initial_gov_token_balance,
+        }
+        | Status::Cancelled {
+            initial_gov_token_balance,
+        }
+        | Status::Expired {
+            initial_gov_token_balance,
+        } => {
+            let contribution = CONTRIBUTIONS
+                .may_load(deps.storage, sender)?
+                .unwrap_or_default();
+            let held_tokens = contribution.minted.saturating_sub(contribution.burned);
+            let paid = contribution.deposited.saturating_sub(contribution.refunded);
+
+            let refunded = if state.unrecorded_contributions {
+                // Contributions made before they were recorded can
+                // not be told apart so every token is refunded at
+                // the fixed price. Tokens are priced at
+                // `initial_gov_token_balance` per `funding_goal` so
+                // invert the ratio to get the funds owed.
+                mul_ratio(tokens, state.funding_goal.amount, initial_gov_token_balance)?
+            } else if !held_tokens.is_zero() && tokens <= held_tokens {
+                mul_ratio(tokens, paid, held_tokens)?
+            } else {
+                return Err(ContractError::RefundExceedsContribution {});
+            };
+            if refunded.is_zero() {
+                return Err(ContractError::SmallRefund {});
+            }
+
+            Ok(SimulateRefundResponse {
+                burned: tokens,
+                refunded,
+            })
+        }
+        Status::Funded { .. } => {
+            // Once a milestone claim is vetoed funding tokens are
+            // refunded their share of the funds not yet released.
+            let vetoed = MILESTONE_PROGRESS
+                .may_load(deps.storage)?
+                .and_then(|progress| progress.vetoed)
+                .ok_or(ContractError::NoRefunds {})?;
+            if tokens > vetoed.outstanding {
+                return Err(ContractError::RefundExceedsContribution {});
+            }
+            Ok(SimulateRefundResponse {
+                burned: tokens,
+                refunded: mul_ratio(tokens, vetoed.unreleased, vetoed.outstanding)?,
+            })
+        }
+    }
+}
+
+/// Computes the outcome of exchanging `tokens` funding tokens for
+/// governance tokens once the campaign has been funded.
+pub fn simulate_swap(
+    deps: Deps,
+    state: &State,
+    block: &BlockInfo,
+    tokens: Uint128,
+) -> Result<SimulateSwapResponse, ContractError> {
+    match current_status(state, block) {
+        Status::Funded { .. } => (),
+        Status::Pending {} | Status::Uninstantiated {} | Status::Aborted {} => {
+            return Err(ContractError::NotOpen {})
+        }
+        Status::Settled {} => return Err(ContractError::Settled {}),
+        _ => return Err(ContractError::NotFunded {}),
+    };
+    if state.milestones.is_some() {
+        let progress = MILESTONE_PROGRESS
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        if progress.vetoed.is_some() {
+            return Err(ContractError::MilestonesVetoed {});
         }
+        // Funds are released by milestone instead.
+        return Ok(SimulateSwapResponse {
+            gov_tokens: tokens,
+            dao_amount: Uint128::zero(),
+            payouts: vec![],
+            fee: Uint128::zero(),
+        });
     }
+
+    // Some math here. TL;DR there will always be a 1:1
+    // relationship between funding tokens and gov tokens.
+    //
+    // price = gov_tokens / funding_goal
+    // => funding_goal * price = gov_tokens
+    //
+    // Once funding goal has been met we will have issued
+    // `gov_tokens` tokens. This is a slightly idealized
+    // example because in real life there is rounding which we
+    // always round down but insofar as what we issue this is
+    // correct.
+    //
+    // Early-bird bonuses are paid out of governance tokens held
+    // back from the sale so the relationship holds with them too.
+    let gov_tokens = tokens;
+
+    // Campaigns may be funded anywhere between their minimum and
+    // maximum goal so pay out the funds actually raised
+    // proportionally to the funding tokens issued for them.
+    let funding_token_addr = FUNDING_TOKEN_ADDR.load(deps.storage)?;
+    let funding_token_info: cw20::TokenInfoResponse = deps
+        .querier
+        .query_wasm_smart(funding_token_addr, &cw20::Cw20QueryMsg::TokenInfo {})?;
+    let payout = mul_ratio(
+        tokens,
+        state.funds_raised.amount,
+        funding_token_info.total_supply,
+    )?;
+    let fee = mul_decimal(payout, state.fee)?;
+    let dao_amount = payout - fee;
+
+    Ok(SimulateSwapResponse {
+        gov_tokens,
+        dao_amount,
+        payouts: split_payout(deps, state, dao_amount)?,
+        fee,
+    })
 }
 
 pub fn get_fee_manager_config(
-    deps: &DepsMut,
+    deps: Deps,
     fee_manager_addr: &Addr,
 ) -> Result<fee_manager::state::Config, ContractError> {
     let response: fee_manager::msg::ConfigResponse = deps
@@ -441,7 +1871,7 @@ pub fn take_public_payment(
     state: &State,
 ) -> Result<Option<BankMsg>, ContractError> {
     // Get fee manager information.
-    let fee_manager_config = get_fee_manager_config(deps, &state.fee_manager_addr)?;
+    let fee_manager_config = get_fee_manager_config(deps.as_ref(), &state.fee_manager_addr)?;
 
     // If there is no fee for public payments, return None.
     if fee_manager_config.public_listing_fee.amount == Uint128::zero() {
@@ -452,7 +1882,9 @@ pub fn take_public_payment(
         .funds
         .iter()
         .filter(|coin| coin.denom == fee_manager_config.public_listing_fee.denom)
-        .fold(Uint128::zero(), |accum, coin| coin.amount + accum);
+        .try_fold(Uint128::zero(), |accum, coin| {
+            accum.checked_add(coin.amount)
+        })?;
 
     if payment != fee_manager_config.public_listing_fee.amount {
         return Err(ContractError::InvalidPublicPayment(format!(
@@ -470,13 +1902,30 @@ pub fn take_public_payment(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
-        QueryMsg::GovTokenAddr {} => query_gov_token_addr(deps),
-        QueryMsg::FundingTokenAddr {} => query_funding_token_addr(deps),
-        QueryMsg::Status {} => query_status(deps),
-        QueryMsg::FundsRaised {} => query_funds_raised(deps),
-        QueryMsg::DumpState {} => query_dump_state(deps),
+        QueryMsg::GovTokenAddr {} => Ok(query_gov_token_addr(deps)?),
+        QueryMsg::FundingTokenAddr {} => Ok(query_funding_token_addr(deps)?),
+        QueryMsg::Status {} => Ok(query_status(deps, env)?),
+        QueryMsg::FundsRaised {} => Ok(query_funds_raised(deps)?),
+        QueryMsg::DumpState {} => Ok(query_dump_state(deps, env)?),
+        QueryMsg::Contributors { start_after, limit } => {
+            Ok(query_contributors(deps, start_after, limit)?)
+        }
+        QueryMsg::Contribution { address } => Ok(query_contribution(deps, address)?),
+        QueryMsg::SimulateFund {
+            address,
+            amount,
+            presale,
+        } => query_simulate_fund(deps, env, address, amount, presale),
+        QueryMsg::SimulateRefund { address, tokens } => {
+            query_simulate_refund(deps, env, address, tokens)
+        }
+        QueryMsg::SimulateSwap { tokens } => query_simulate_swap(deps, env, tokens),
+        QueryMsg::Vesting { address } => Ok(query_vesting(deps, env, address)?),
+        QueryMsg::Milestones {} => Ok(query_milestones(deps)?),
+        QueryMsg::Poll {} => Ok(to_binary(&POLL.may_load(deps.storage)?)?),
+        QueryMsg::Vote { address } => Ok(query_vote(deps, address)?),
     }
 }
 
@@ -490,9 +1939,9 @@ pub fn query_funding_token_addr(deps: Deps) -> StdResult<Binary> {
     to_binary(&funding_token_addr)
 }
 
-pub fn query_status(deps: Deps) -> StdResult<Binary> {
+pub fn query_status(deps: Deps, env: Env) -> StdResult<Binary> {
     let state = STATE.load(deps.storage)?;
-    to_binary(&state.status)
+    to_binary(&current_status(&state, &env.block))
 }
 
 pub fn query_funds_raised(deps: Deps) -> StdResult<Binary> {
@@ -500,7 +1949,7 @@ pub fn query_funds_raised(deps: Deps) -> StdResult<Binary> {
     to_binary(&state.funds_raised)
 }
 
-pub fn query_dump_state(deps: Deps) -> StdResult<Binary> {
+pub fn query_dump_state(deps: Deps, env: Env) -> StdResult<Binary> {
     let state = STATE.load(deps.storage)?;
     let funding_token_addr = FUNDING_TOKEN_ADDR.load(deps.storage)?;
     let gov_token_addr = GOV_TOKEN_ADDR.load(deps.storage)?;
@@ -514,13 +1963,56 @@ pub fn query_dump_state(deps: Deps) -> StdResult<Binary> {
         .querier
         .query_wasm_smart(gov_token_addr.clone(), &cw20::Cw20QueryMsg::TokenInfo {})?;
 
+    let status = current_status(&state, &env.block);
+    let token_price = match status {
+        Status::Open {
+            initial_gov_token_balance,
+        }
+        | Status::Cancelled {
+            initial_gov_token_balance,
+        }
+        | Status::Funded {
+            initial_gov_token_balance,
+        }
+        | Status::Expired {
+            initial_gov_token_balance,
+        } => sale_reserve(&state.bonus_windows, initial_gov_token_balance)
+            .ok()
+            .and_then(|for_sale| {
+                state
+                    .pricing
+                    .fixed_price(state.funding_goal.amount, for_sale)
+            }),
+        _ => None,
+    };
+
     to_binary(&DumpStateResponse {
-        status: state.status,
+        status,
+        token_price,
         dao_addr: state.dao_addr,
+        dao_type: state.dao_type,
         fee_manager_addr: state.fee_manager_addr,
+        fee: state.fee,
+        fee_receiver: state.fee_receiver,
+        funding_denom: state.funding_denom,
         funding_goal: state.funding_goal,
+        min_goal: state.min_goal,
+        pricing: state.pricing,
+        bonus_windows: state.bonus_windows,
+        min_contribution: state.min_contribution,
+        max_contribution_per_address: state.max_contribution_per_address,
+        presale: state.presale,
+        vesting: state.vesting,
+        milestones: state.milestones,
+        cancellation: state.cancellation,
+        registry: state.registry,
+        unrecorded_contributions: state.unrecorded_contributions,
+        opened_at: state.opened_at,
+        funded_at: state.funded_at,
         creator: state.creator,
+        beneficiaries: state.beneficiaries,
         funds_raised: state.funds_raised,
+        deadline: state.deadline,
         funding_token_info,
         gov_token_info,
         campaign_info: state.campaign_info,
@@ -530,6 +2022,110 @@ pub fn query_dump_state(deps: Deps) -> StdResult<Binary> {
     })
 }
 
+pub fn query_contributors(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let min = start_after
+        .as_ref()
+        .map(|addr| Bound::exclusive(addr.as_str()));
+
+    let contributors = CONTRIBUTIONS
+        .range(deps.storage, min, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(address, contribution)| ContributorResponse {
+                address,
+                contribution,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ContributorsResponse { contributors })
+}
+
+pub fn query_contribution(deps: Deps, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let contribution = CONTRIBUTIONS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    to_binary(&contribution)
+}
+
+pub fn query_simulate_fund(
+    deps: Deps,
+    env: Env,
+    address: String,
+    amount: Uint128,
+    presale: Option<PresaleProof>,
+) -> Result<Binary, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let address = deps.api.addr_validate(&address)?;
+    Ok(to_binary(&simulate_fund(
+        deps,
+        &state,
+        &env.block,
+        &address,
+        amount,
+        presale.as_ref(),
+    )?)?)
+}
+
+pub fn query_simulate_refund(
+    deps: Deps,
+    env: Env,
+    address: String,
+    tokens: Uint128,
+) -> Result<Binary, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let address = deps.api.addr_validate(&address)?;
+    Ok(to_binary(&simulate_refund(
+        deps, &state, &env.block, &address, tokens,
+    )?)?)
+}
+
+pub fn query_simulate_swap(deps: Deps, env: Env, tokens: Uint128) -> Result<Binary, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    Ok(to_binary(&simulate_swap(
+        deps, &state, &env.block, tokens,
+    )?)?)
+}
+
+pub fn query_vesting(deps: Deps, env: Env, address: String) -> StdResult<Binary> {
+    let state = STATE.load(deps.storage)?;
+    let address = deps.api.addr_validate(&address)?;
+    to_binary(&vesting_of(deps, &state, &env.block, &address)?)
+}
+
+pub fn query_milestones(deps: Deps) -> StdResult<Binary> {
+    let state = STATE.load(deps.storage)?;
+    to_binary(&MilestonesResponse {
+        config: state.milestones,
+        progress: MILESTONE_PROGRESS
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+    })
+}
+
+pub fn query_vote(deps: Deps, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    to_binary(&VOTES.may_load(deps.storage, &address)?)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    crate::migrations::migrate(deps, &env, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default()
+        .add_attribute("method", "migrate")
+        .add_attribute("version", CONTRACT_VERSION))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
@@ -548,6 +2144,12 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
                 .add_attribute("method", "reply")
                 .add_attribute("funding_token", token_addr))
         }
+        REGISTRY_CALLBACK_REPLY_ID => {
+            let error = msg.result.into_result().err().unwrap_or_default();
+            Ok(Response::default()
+                .add_attribute("method", "reply")
+                .add_attribute("registry_error", error))
+        }
         _ => Err(ContractError::UnknownReplyId { id: msg.id }),
     }
 }
@@ -0,0 +1,106 @@
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The architecture of the DAO running a campaign.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DaoType {
+    /// A DAO DAO v0.2.x cw3-dao contract.
+    Cw3Dao {},
+    /// A cw-core contract whose voting module is backed by staked
+    /// cw20 tokens.
+    CwCore {},
+}
+
+impl Default for DaoType {
+    fn default() -> Self {
+        DaoType::Cw3Dao {}
+    }
+}
+
+impl DaoType {
+    pub fn adapter(&self) -> &'static dyn DaoAdapter {
+        match self {
+            DaoType::Cw3Dao {} => &Cw3Dao,
+            DaoType::CwCore {} => &CwCore,
+        }
+    }
+}
+
+/// Resolves the contracts a campaign interacts with from the address
+/// of its DAO.
+pub trait DaoAdapter {
+    /// Gets the address of the DAO's governance token.
+    fn gov_token(&self, querier: &QuerierWrapper, dao: &Addr) -> StdResult<Addr>;
+    /// Gets the address of the contract governance tokens are staked
+    /// in.
+    fn staking_contract(&self, querier: &QuerierWrapper, dao: &Addr) -> StdResult<Addr>;
+    /// Gets the address funds raised are sent to.
+    fn treasury(&self, querier: &QuerierWrapper, dao: &Addr) -> StdResult<Addr>;
+}
+
+pub struct Cw3Dao;
+
+impl DaoAdapter for Cw3Dao {
+    fn gov_token(&self, querier: &QuerierWrapper, dao: &Addr) -> StdResult<Addr> {
+        let config: cw3_dao::query::ConfigResponse =
+            querier.query_wasm_smart(dao, &cw3_dao::msg::QueryMsg::GetConfig {})?;
+        Ok(config.gov_token)
+    }
+
+    fn staking_contract(&self, querier: &QuerierWrapper, dao: &Addr) -> StdResult<Addr> {
+        let config: cw3_dao::query::ConfigResponse =
+            querier.query_wasm_smart(dao, &cw3_dao::msg::QueryMsg::GetConfig {})?;
+        Ok(config.staking_contract)
+    }
+
+    fn treasury(&self, _querier: &QuerierWrapper, dao: &Addr) -> StdResult<Addr> {
+        Ok(dao.clone())
+    }
+}
+
+/// The subset of the cw-core query interface used to find a DAO's
+/// voting module.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CwCoreQueryMsg {
+    /// Returns Addr.
+    VotingModule {},
+}
+
+/// The subset of the cw20 staked balance voting module query
+/// interface used to find a DAO's governance token.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VotingModuleQueryMsg {
+    /// Returns Addr.
+    TokenContract {},
+    /// Returns Addr.
+    StakingContract {},
+}
+
+pub struct CwCore;
+
+impl CwCore {
+    fn voting_module(querier: &QuerierWrapper, dao: &Addr) -> StdResult<Addr> {
+        querier.query_wasm_smart(dao, &CwCoreQueryMsg::VotingModule {})
+    }
+}
+
+impl DaoAdapter for CwCore {
+    fn gov_token(&self, querier: &QuerierWrapper, dao: &Addr) -> StdResult<Addr> {
+        let voting_module = CwCore::voting_module(querier, dao)?;
+        querier.query_wasm_smart(voting_module, &VotingModuleQueryMsg::TokenContract {})
+    }
+
+    fn staking_contract(&self, querier: &QuerierWrapper, dao: &Addr) -> StdResult<Addr> {
+        let voting_module = CwCore::voting_module(querier, dao)?;
+        querier.query_wasm_smart(voting_module, &VotingModuleQueryMsg::StakingContract {})
+    }
+
+    /// cw-core contracts hold the DAO's treasury themselves.
+    fn treasury(&self, _querier: &QuerierWrapper, dao: &Addr) -> StdResult<Addr> {
+        Ok(dao.clone())
+    }
+}
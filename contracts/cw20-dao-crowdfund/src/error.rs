@@ -1,4 +1,4 @@
-use cosmwasm_std::{Decimal, StdError};
+use cosmwasm_std::{Decimal, DivideByZeroError, OverflowError, StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -24,24 +24,162 @@ pub enum ContractError {
     #[error("Campaign has been closed by the DAO. You can still receive a refund.")]
     Closed {},
 
-    #[error("Funding overflow. A campaign can't be funded past its funding goal.")]
-    FundingOverflow {},
+    #[error("Campaign deadline has passed. You can still receive a refund.")]
+    Expired {},
 
-    #[error("Contribution amount is too small to receive any gov tokens. Gov token price: ({token_price}) tokens/juno")]
-    SmallContribution { token_price: Decimal },
+    #[error("Funded campaigns can not be refunded.")]
+    NoRefunds {},
 
-    #[error("Refund token amount too small. Would result in a refund of zero. Must refund at least ({token_price}) tokens.")]
-    SmallRefund { token_price: Decimal },
+    #[error("Refunds can only be processed for cancelled or expired campaigns.")]
+    InvalidProcessRefunds {},
+
+    #[error("Refunds can not be processed for campaigns with unrecorded contributions. Funding token holders must refund themselves.")]
+    NoBatchRefunds {},
+
+    #[error(
+        "Funding tokens can only be exchanged for governance tokens once the campaign is funded."
+    )]
+    NotFunded {},
+
+    #[error("Contribution is smaller than the minimum contribution of ({min}).")]
+    ContributionTooSmall { min: Uint128 },
+
+    #[error("Contribution would exceed the limit of ({max}) per address. At most ({remaining}) more may be contributed.")]
+    ContributionLimitExceeded { max: Uint128, remaining: Uint128 },
+
+    #[error("Only allowlisted addresses may fund the campaign during its presale.")]
+    PresaleOnly {},
+
+    #[error("Contribution would exceed the presale allocation of ({allocation}). At most ({remaining}) more may be contributed.")]
+    PresaleAllocationExceeded {
+        allocation: Uint128,
+        remaining: Uint128,
+    },
+
+    #[error("Invalid presale proof.")]
+    InvalidProof {},
+
+    #[error("Invalid presale Merkle root. Must be a hex encoded sha256 hash.")]
+    InvalidMerkleRoot {},
+
+    #[error("This campaign does not have a presale.")]
+    NoPresale {},
+
+    #[error("The presale has ended.")]
+    PresaleEnded {},
+
+    #[error("No governance tokens have vested that have not been claimed.")]
+    NothingToClaim {},
+
+    #[error("This campaign does not release funds by milestone.")]
+    NoMilestones {},
+
+    #[error("Every milestone has been claimed.")]
+    MilestonesComplete {},
+
+    #[error("A milestone claim is already pending.")]
+    MilestoneClaimPending {},
+
+    #[error("There is no pending milestone claim.")]
+    NoMilestoneClaim {},
+
+    #[error("The milestone claim may still be vetoed.")]
+    ChallengePeriodActive {},
+
+    #[error("A milestone claim was vetoed. Funding tokens may be returned for a refund.")]
+    MilestonesVetoed {},
+
+    #[error("There is no open poll to vote in.")]
+    NoPoll {},
+
+    #[error("Withdraw your vote from an earlier poll before voting again.")]
+    StaleVote {},
+
+    #[error("Votes may not be withdrawn while their poll is open.")]
+    VoteLocked {},
+
+    #[error("No vote to withdraw.")]
+    NoVote {},
+
+    #[error("Another poll is already open.")]
+    PollOpen {},
+
+    #[error("Campaign can not be cancelled by its contributors.")]
+    NoCancellation {},
+
+    #[error("This campaign does not accept the asset sent.")]
+    InvalidFundingAsset {},
+
+    #[error("Contribution amount is too small to receive any gov tokens.")]
+    SmallContribution {},
+
+    #[error("Refund token amount too small. Would result in a refund of zero.")]
+    SmallRefund {},
 
     #[error("Only open campaigns can be closed.")]
     InvalidClose {},
 
+    #[error("Only campaigns that have not opened can be aborted.")]
+    InvalidAbort {},
+
+    #[error("Campaign can not be settled: {0}.")]
+    InvalidSettle(String),
+
+    #[error("Campaign has been settled. Funding tokens can no longer be exchanged.")]
+    Settled {},
+
+    #[error("Only open campaigns whose deadline has passed can be finalized. The DAO may finalize campaigns that have met their minimum goal early.")]
+    InvalidFinalize {},
+
     #[error("Too few gov tokens sent. This would result in a funding token price of zero.")]
     InvalidGovTokenAmount {},
 
+    #[error(
+        "Too few gov tokens sent to cover the pricing curve. At least ({required}) are required."
+    )]
+    InsufficientGovTokens { required: Uint128 },
+
+    #[error("Refunds are only available for funding tokens issued to the sender.")]
+    RefundExceedsContribution {},
+
     #[error("Invalid public payment: {0}.")]
     InvalidPublicPayment(String),
 
+    #[error("Invalid beneficiaries: {0}.")]
+    InvalidBeneficiaries(String),
+
     #[error("Invalid fee manager address.")]
     InvalidFeeManager,
+
+    #[error("The fee manager does not offer a lower fee than the campaign's ({fee}).")]
+    NoFeeReduction { fee: Decimal },
+
+    #[error("Can not migrate from a different contract ({contract}).")]
+    MigrationContractMismatch { contract: String },
+
+    #[error("Can not migrate from version ({from}) to older version ({to}).")]
+    MigrationDowngrade { from: String, to: String },
+
+    #[error("Can not migrate from version ({from}). Campaigns must be at version 0.3.0 or later.")]
+    MigrationUnsupported { from: String },
+
+    #[error("Invalid contract version ({0}).")]
+    InvalidVersion(String),
+
+    #[error(
+        "Arithmetic overflow. The amounts involved are too large for the campaign to account for."
+    )]
+    Overflow {},
+}
+
+impl From<OverflowError> for ContractError {
+    fn from(_: OverflowError) -> Self {
+        ContractError::Overflow {}
+    }
+}
+
+impl From<DivideByZeroError> for ContractError {
+    fn from(err: DivideByZeroError) -> Self {
+        ContractError::Std(err.into())
+    }
 }
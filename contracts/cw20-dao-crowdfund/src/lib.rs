@@ -1,7 +1,14 @@
 pub mod contract;
+pub mod dao;
 mod error;
+pub mod migrations;
+pub mod milestones;
 pub mod msg;
+pub mod poll;
+pub mod presale;
+pub mod pricing;
 pub mod state;
+pub mod vesting;
 
 #[cfg(test)]
 mod tests;
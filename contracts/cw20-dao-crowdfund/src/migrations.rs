@@ -0,0 +1,212 @@
+use cosmwasm_std::{DepsMut, Env, QuerierWrapper, Storage};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::Denom;
+
+use crate::dao::DaoType;
+use crate::error::ContractError;
+use crate::pricing::PricingCurve;
+use crate::state::{State, Status, STATE};
+
+/// A storage migration. Migrations are applied to campaigns whose
+/// stored contract version is older than the version the migration
+/// was introduced in. The querier is available for migrations that
+/// need to snapshot state held by other contracts and the
+/// environment for those that need the time of the migration.
+type Migration = fn(&mut dyn Storage, &QuerierWrapper, &Env) -> Result<(), ContractError>;
+
+/// Migrations in the order they must be applied. Each entry is the
+/// version that introduced a new storage layout along with the
+/// function that moves the previous layout to it.
+const MIGRATIONS: &[(&str, Migration)] = &[("0.4.0", migrate_v0_3_0)];
+
+/// The oldest version campaigns may be migrated from. Storage
+/// layouts older than this have no migration to the current one.
+const OLDEST_SUPPORTED: &str = "0.3.0";
+
+/// Migrates a campaign from its stored contract version to
+/// `version`, applying every storage migration introduced since the
+/// stored version in order. Migrating between contracts, to older
+/// versions, and from versions older than 0.3.0 is not supported.
+pub fn migrate(
+    deps: DepsMut,
+    env: &Env,
+    contract_name: &str,
+    version: &str,
+) -> Result<(), ContractError> {
+    let storage = deps.storage;
+    let stored = get_contract_version(storage)?;
+    if stored.contract != contract_name {
+        return Err(ContractError::MigrationContractMismatch {
+            contract: stored.contract,
+        });
+    }
+
+    let from = parse_version(&stored.version)?;
+    let to = parse_version(version)?;
+    if from > to {
+        return Err(ContractError::MigrationDowngrade {
+            from: stored.version,
+            to: version.to_string(),
+        });
+    }
+    if from < parse_version(OLDEST_SUPPORTED)? {
+        return Err(ContractError::MigrationUnsupported {
+            from: stored.version,
+        });
+    }
+
+    for &(introduced, migration) in MIGRATIONS {
+        let introduced = parse_version(introduced)?;
+        if from < introduced && introduced <= to {
+            migration(storage, &deps.querier, env)?;
+        }
+    }
+
+    set_contract_version(storage, contract_name, version)?;
+    Ok(())
+}
+
+/// Parses a `major.minor.patch` version string into a tuple that
+/// can be compared.
+fn parse_version(version: &str) -> Result<(u64, u64, u64), ContractError> {
+    let parts = version
+        .split('.')
+        .map(|part| part.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ContractError::InvalidVersion(version.to_string()))?;
+    match parts[..] {
+        [major, minor, patch] => Ok((major, minor, patch)),
+        _ => Err(ContractError::InvalidVersion(version.to_string())),
+    }
+}
+
+/// Storage layout of campaigns created by version 0.3.0 of the
+/// contract.
+pub mod v0_3_0 {
+    use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
+    use cw_storage_plus::Item;
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::state::Campaign;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Status {
+        Uninstantiated {},
+        Pending {},
+        Open {
+            token_price: Decimal,
+            initial_gov_token_balance: Uint128,
+        },
+        Cancelled {
+            token_price: Decimal,
+            initial_gov_token_balance: Uint128,
+        },
+        Funded {
+            token_price: Decimal,
+            initial_gov_token_balance: Uint128,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct State {
+        pub status: Status,
+
+        pub dao_addr: Addr,
+        pub fee_manager_addr: Addr,
+        pub creator: Addr,
+
+        pub funding_goal: Coin,
+        pub funds_raised: Coin,
+
+        pub campaign_info: Campaign,
+    }
+
+    pub const STATE: Item<State> = Item::new("state");
+}
+
+fn migrate_v0_3_0(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+) -> Result<(), ContractError> {
+    let old = v0_3_0::STATE.load(storage)?;
+
+    // Campaigns created before fees were fixed at creation take the
+    // fee manager's terms at the time of the migration.
+    let fee_manager: fee_manager::msg::ConfigResponse = querier
+        .query_wasm_smart(
+            old.fee_manager_addr.clone(),
+            &fee_manager::msg::QueryMsg::GetConfig {},
+        )
+        .or(Err(ContractError::InvalidFeeManager))?;
+
+    let status = match old.status {
+        v0_3_0::Status::Uninstantiated {} => Status::Uninstantiated {},
+        v0_3_0::Status::Pending {} => Status::Pending {},
+        v0_3_0::Status::Open {
+            initial_gov_token_balance,
+            ..
+        } => Status::Open {
+            initial_gov_token_balance,
+        },
+        v0_3_0::Status::Cancelled {
+            initial_gov_token_balance,
+            ..
+        } => Status::Cancelled {
+            initial_gov_token_balance,
+        },
+        v0_3_0::Status::Funded {
+            initial_gov_token_balance,
+            ..
+        } => Status::Funded {
+            initial_gov_token_balance,
+        },
+    };
+
+    // Contributions to campaigns that have opened were not recorded.
+    let unrecorded_contributions =
+        !matches!(status, Status::Uninstantiated {} | Status::Pending {});
+
+    // When funded campaigns were funded was not recorded. Count from
+    // the migration so that they may be settled after the grace
+    // period.
+    let funded_at = match status {
+        Status::Funded { .. } => Some(env.block.time),
+        _ => None,
+    };
+
+    STATE.save(
+        storage,
+        &State {
+            status,
+            dao_addr: old.dao_addr,
+            dao_type: DaoType::Cw3Dao {},
+            fee_manager_addr: old.fee_manager_addr,
+            fee: fee_manager.config.fee,
+            fee_receiver: fee_manager.config.fee_receiver,
+            creator: old.creator,
+            beneficiaries: vec![],
+            funding_denom: Denom::Native(old.funding_goal.denom.clone()),
+            min_goal: old.funding_goal.amount,
+            pricing: PricingCurve::Fixed {},
+            bonus_windows: vec![],
+            min_contribution: None,
+            max_contribution_per_address: None,
+            presale: None,
+            vesting: None,
+            milestones: None,
+            cancellation: None,
+            registry: None,
+            funding_goal: old.funding_goal,
+            funds_raised: old.funds_raised,
+            unrecorded_contributions,
+            deadline: None,
+            opened_at: None,
+            funded_at,
+            campaign_info: old.campaign_info,
+        },
+    )?;
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use cosmwasm_std::{Decimal, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+
+/// A tranche of the funds raised that the DAO may claim once it has
+/// completed the milestone described.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Milestone {
+    pub description: String,
+    /// The share of the funds raised released by this milestone.
+    pub release: Decimal,
+}
+
+/// Holds funds raised in the campaign after it is funded and
+/// releases them to the DAO as it completes milestones. Funding
+/// token holders may veto each claim during its challenge period.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MilestoneConfig {
+    /// Milestones in the order they must be claimed. Their releases
+    /// must add up to one.
+    pub milestones: Vec<Milestone>,
+    /// Seconds funding token holders have to veto a claim.
+    pub challenge_period: u64,
+    /// The share of outstanding funding tokens that must vote to
+    /// veto a claim.
+    pub veto_threshold: Decimal,
+}
+
+impl MilestoneConfig {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        let invalid = |reason: &str| Err(ContractError::Instantiation(reason.to_string()));
+        if self.milestones.is_empty() {
+            return invalid("milestones can not be empty");
+        }
+        if self
+            .milestones
+            .iter()
+            .any(|milestone| milestone.release.is_zero())
+        {
+            return invalid("milestones must release a non-zero share of funds");
+        }
+        let total = self
+            .milestones
+            .iter()
+            .fold(Decimal::zero(), |total, milestone| {
+                total + milestone.release
+            });
+        if total != Decimal::one() {
+            return invalid("milestone releases must add up to one");
+        }
+        if self.challenge_period == 0 {
+            return invalid("milestone challenge period must be non-zero");
+        }
+        if self.veto_threshold.is_zero() || self.veto_threshold > Decimal::one() {
+            return invalid("milestone veto threshold must be between zero and one");
+        }
+        Ok(())
+    }
+}
+
+/// A claim on a milestone's tranche waiting out its challenge
+/// period.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MilestoneClaim {
+    /// The index of the milestone claimed.
+    pub milestone: u32,
+    /// The funds that will be released.
+    pub amount: Uint128,
+    /// The poll funding token holders may veto the claim in.
+    pub poll: u64,
+}
+
+/// Funds left to refund after a claim has been vetoed. Refunds are
+/// paid at `unreleased / outstanding` per funding token and both are
+/// reduced as refunds are made.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VetoRefunds {
+    pub unreleased: Uint128,
+    pub outstanding: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct MilestoneProgress {
+    /// The number of milestones whose funds have been released.
+    pub completed: u32,
+    /// Funds released to the DAO, including fees.
+    pub released: Uint128,
+    pub claim: Option<MilestoneClaim>,
+    /// Set once a claim is vetoed. No further milestones may be
+    /// claimed and funding tokens may be returned for a refund.
+    pub vetoed: Option<VetoRefunds>,
+}
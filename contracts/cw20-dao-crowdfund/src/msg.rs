@@ -1,21 +1,77 @@
-use cosmwasm_std::{Addr, Coin};
-use cw20::Cw20ReceiveMsg;
+use cosmwasm_std::{Addr, Coin, Decimal, Timestamp, Uint128};
+use cw20::{Cw20ReceiveMsg, Denom};
+use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::{Campaign, Status};
+use crate::dao::DaoType;
+use crate::milestones::{MilestoneConfig, MilestoneProgress};
+use crate::poll::CancellationConfig;
+use crate::presale::Presale;
+use crate::pricing::{BonusWindow, PricingCurve};
+use crate::state::{Beneficiary, Campaign, Contribution, Status};
+use crate::vesting::VestingConfig;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub dao_address: String,
+    /// The architecture of the DAO at `dao_address`. Defaults to a
+    /// DAO DAO v0.2.x cw3-dao contract.
+    pub dao_type: Option<DaoType>,
     pub fee_manager_address: String,
+    /// Addresses the DAO's share of funds raised is split between
+    /// by weight. Defaults to sending it all to the DAO's treasury.
+    pub beneficiaries: Option<Vec<BeneficiaryMsg>>,
     /// Code ID for the cw20 contract we should use for the
     /// fundraising token.
     pub cw20_code_id: u64,
 
+    /// The maximum amount the campaign may raise. The campaign is
+    /// funded as soon as it is reached.
     pub funding_goal: Coin,
+    /// Address of a cw20 token to raise funds in. If set,
+    /// `funding_goal.denom` must be this address. Defaults to raising
+    /// funds in the native `funding_goal.denom`.
+    pub funding_cw20: Option<String>,
+    /// The amount the campaign must raise to be funded once its
+    /// deadline passes or the DAO finalizes it. Defaults to
+    /// `funding_goal`.
+    pub min_goal: Option<Uint128>,
+    /// Determines the number of funding tokens issued for each
+    /// contribution. Defaults to a fixed price that sells all
+    /// governance tokens sent by the DAO at the funding goal.
+    pub pricing: Option<PricingCurve>,
+    /// Early-bird bonuses for contributions made shortly after the
+    /// campaign opens. The DAO must send enough governance tokens to
+    /// pay the largest bonus on every token sold.
+    pub bonus_windows: Option<Vec<BonusWindow>>,
+    /// The smallest contribution accepted. The contribution that
+    /// completes the campaign may be smaller.
+    pub min_contribution: Option<Uint128>,
+    /// The most any one address may contribute, net of refunds.
+    pub max_contribution_per_address: Option<Uint128>,
+    /// An optional presale phase during which only allowlisted
+    /// addresses may fund the campaign.
+    pub presale: Option<Presale>,
+    /// An optional vesting schedule for governance tokens received
+    /// in exchange for funding tokens.
+    pub vesting: Option<VestingConfig>,
+    /// If set, funds raised are held by the campaign and released to
+    /// the DAO in tranches as it completes milestones.
+    pub milestones: Option<MilestoneConfig>,
+    /// If set, funding token holders may vote to cancel the campaign
+    /// while it is open.
+    pub cancellation: Option<CancellationConfig>,
+    /// A dao-up-registry contract listing the campaign. It is
+    /// notified whenever the campaign's status or visibility changes
+    /// and must be the contract instantiating the campaign.
+    pub registry: Option<String>,
     pub funding_token_name: String,
     pub funding_token_symbol: String,
+    /// Block height or time after which the campaign stops accepting
+    /// funds. If the minimum goal has not been met by then the
+    /// campaign expires and backers may refund their contributions.
+    pub deadline: Option<Expiration>,
 
     pub campaign_info: Campaign,
 }
@@ -25,24 +81,121 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     /// Deposits funds to the contract and mints tokens for the
     /// sender. Can only be executed if the campaign is not closed.
+    /// Funds beyond what is needed to reach the funding goal are
+    /// refunded.
+    ///
+    /// Campaigns raising funds in a cw20 token are funded by sending
+    /// the token to the contract instead.
     Fund {},
+    /// Funds the campaign during its presale. `proof` must show that
+    /// the sender may contribute up to `allocation`. Behaves like
+    /// `Fund` once the presale has ended.
+    FundPresale {
+        allocation: Uint128,
+        proof: Vec<String>,
+    },
     /// Used for issuing refunds, swaping to governance tokens, and
     /// the initial funding of the contract by the DAO.
     ///
     /// Sending funding tokens to the contract will execute a refund
-    /// if the campaign is open. If the campaign is closed it will
-    /// give staked governance tokens to the sender.
+    /// if the campaign is open. If the campaign is funded it will
+    /// give governance tokens to the sender. The tokens are not
+    /// staked: stake-cw20 only stakes tokens for the address that
+    /// sends them, so the sender must stake them in the DAO
+    /// themselves to gain voting power.
     ///
     /// Sending governance tokens to the contract will seed the
     /// contract and put it in an open state. The DAO must do this
     /// before the campaign can begin.
+    ///
+    /// Sending the funding asset of a campaign raising funds in a
+    /// cw20 token will fund the campaign.
     Receive(Cw20ReceiveMsg),
     /// Closes the campaign and returns governance tokens to the
     /// DAO. Refunds are still accepted but funding is no longer
     /// possible.
     Close {},
+    /// Aborts a campaign that has not opened and returns any
+    /// governance tokens it holds to the DAO. Only the DAO may do
+    /// this.
+    Abort {},
     /// Replaces the campaign's campaign information with `campaign`.
     UpdateCampaign { campaign: Campaign },
+    /// Stops an open campaign from accepting funds. The campaign is
+    /// funded if it has met its minimum goal and expires
+    /// otherwise. Governance tokens that will not be swapped are
+    /// returned to the DAO. May be executed by anyone once the
+    /// deadline has passed, or by the DAO once the minimum goal has
+    /// been met.
+    Finalize {},
+    /// Sweeps governance tokens and funds left in a funded campaign
+    /// to the DAO and marks it settled. Allowed once every funding
+    /// token has been exchanged or a year after the campaign was
+    /// funded. Only the DAO may do this.
+    Settle {},
+    /// Refunds up to `limit` contributors to a cancelled or expired
+    /// campaign, continuing from where the last batch stopped.
+    /// Contributors are refunded what they paid from the ledger and
+    /// their funding tokens are left unburned but can no longer be
+    /// refunded. May be executed by anyone.
+    ProcessRefunds { limit: Option<u32> },
+    /// Adopts the fee manager's current fee terms if they charge a
+    /// lower fee than the campaign's. Only the DAO may do this.
+    AcceptFeeReduction {},
+    /// Replaces the Merkle root of the presale allowlist. Only the
+    /// DAO may do this and only until the presale ends.
+    UpdatePresaleMerkleRoot { merkle_root: String },
+    /// Sends the sender the governance tokens that have vested for
+    /// them and not yet been claimed.
+    ClaimVested {},
+    /// Replaces the addresses the DAO's share of funds raised is
+    /// split between. An empty list sends it all to the DAO's
+    /// treasury. Only the DAO may do this.
+    UpdateBeneficiaries { beneficiaries: Vec<BeneficiaryMsg> },
+    /// Claims the next milestone's tranche of funds and opens a poll
+    /// in which funding token holders may veto the claim during the
+    /// challenge period. Only the DAO may do this.
+    ClaimMilestone {},
+    /// Releases the funds of a milestone claim whose challenge
+    /// period has passed without a veto. May be executed by anyone.
+    ReleaseMilestone {},
+    /// Returns funding tokens locked in a vote to the sender once
+    /// its poll has closed.
+    WithdrawVote {},
+    /// Opens a poll in which funding token holders may vote to
+    /// cancel the campaign. Only holders of funding tokens may do
+    /// this while the campaign is open.
+    OpenCancellationPoll {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BeneficiaryMsg {
+    pub address: String,
+    pub weight: u64,
+}
+
+/// Messages that may be attached when sending the funding cw20 of a
+/// campaign raising funds in a cw20 token. Sends without one fund
+/// the campaign.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// See `ExecuteMsg::FundPresale`.
+    FundPresale {
+        allocation: Uint128,
+        proof: Vec<String>,
+    },
+    /// Sent with funding tokens to lock them in favour of the open
+    /// poll.
+    Vote {},
+}
+
+/// Proof that an address may contribute up to `allocation` during a
+/// campaign's presale.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PresaleProof {
+    pub allocation: Uint128,
+    pub proof: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -59,17 +212,148 @@ pub enum QueryMsg {
     FundsRaised {},
     /// Dumps the contracts state. Returns DumpStateResponse.
     DumpState {},
+    /// Lists the addresses that have interacted with the campaign
+    /// and their contributions, ordered by address. Returns
+    /// ContributorsResponse.
+    Contributors {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Gets an address's contribution to the campaign. Returns
+    /// Contribution.
+    Contribution { address: String },
+    /// Simulates `address` contributing `amount` to the campaign.
+    /// `presale` must be set to simulate contributions during the
+    /// presale. Returns SimulateFundResponse.
+    SimulateFund {
+        address: String,
+        amount: Uint128,
+        presale: Option<PresaleProof>,
+    },
+    /// Simulates `address` returning `tokens` funding tokens for a
+    /// refund. Returns SimulateRefundResponse.
+    SimulateRefund { address: String, tokens: Uint128 },
+    /// Simulates exchanging `tokens` funding tokens for governance
+    /// tokens. Returns SimulateSwapResponse.
+    SimulateSwap { tokens: Uint128 },
+    /// Gets the governance tokens vesting for an address. Returns
+    /// VestingResponse.
+    Vesting { address: String },
+    /// Gets the campaign's milestones and its progress through
+    /// them. Returns MilestonesResponse.
+    Milestones {},
+    /// Gets the most recently opened poll. Returns Option<Poll>.
+    Poll {},
+    /// Gets the funding tokens an address has locked in a
+    /// vote. Returns Option<LockedVote>.
+    Vote { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateFundResponse {
+    /// The amount the campaign would accept.
+    pub accepted: Uint128,
+    /// The amount that would be refunded for exceeding the funding
+    /// goal.
+    pub refunded: Uint128,
+    /// The number of funding tokens that would be minted.
+    pub minted: Uint128,
+    /// Of `minted`, the number of tokens that would be an early-bird
+    /// bonus.
+    pub bonus: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateRefundResponse {
+    /// The number of funding tokens that would be burned.
+    pub burned: Uint128,
+    /// The amount that would be refunded.
+    pub refunded: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateSwapResponse {
+    /// The number of governance tokens that would be sent.
+    pub gov_tokens: Uint128,
+    /// The amount that would be sent to the DAO.
+    pub dao_amount: Uint128,
+    /// How `dao_amount` would be split between the DAO's
+    /// beneficiaries.
+    pub payouts: Vec<Payout>,
+    /// The fee that would be sent to the fee receiver.
+    pub fee: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Payout {
+    pub address: Addr,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingResponse {
+    /// Governance tokens vesting for the address.
+    pub total: Uint128,
+    /// Of `total`, the number of tokens that have vested.
+    pub vested: Uint128,
+    /// Of `vested`, the number of tokens claimed.
+    pub claimed: Uint128,
+    /// The number of tokens that may be claimed now.
+    pub claimable: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MilestonesResponse {
+    pub config: Option<MilestoneConfig>,
+    pub progress: MilestoneProgress,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContributorResponse {
+    pub address: Addr,
+    pub contribution: Contribution,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContributorsResponse {
+    pub contributors: Vec<ContributorResponse>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct DumpStateResponse {
     pub status: Status,
+    /// Funding tokens issued per unit of funds before any bonus,
+    /// rounded down. Only set for fixed price campaigns once they
+    /// have opened.
+    pub token_price: Option<Decimal>,
     pub dao_addr: Addr,
+    pub dao_type: DaoType,
     pub fee_manager_addr: Addr,
+    pub fee: Decimal,
+    pub fee_receiver: Addr,
     pub creator: Addr,
+    pub beneficiaries: Vec<Beneficiary>,
+    pub funding_denom: Denom,
     pub funding_goal: Coin,
+    pub min_goal: Uint128,
+    pub pricing: PricingCurve,
+    pub bonus_windows: Vec<BonusWindow>,
+    pub min_contribution: Option<Uint128>,
+    pub max_contribution_per_address: Option<Uint128>,
+    pub presale: Option<Presale>,
+    pub vesting: Option<VestingConfig>,
+    pub milestones: Option<MilestoneConfig>,
+    pub cancellation: Option<CancellationConfig>,
+    pub registry: Option<Addr>,
     pub funds_raised: Coin,
+    pub unrecorded_contributions: bool,
+    pub deadline: Option<Expiration>,
+    pub opened_at: Option<Timestamp>,
+    pub funded_at: Option<Timestamp>,
     pub funding_token_info: cw20::TokenInfoResponse,
     pub gov_token_info: cw20::TokenInfoResponse,
     pub campaign_info: Campaign,
@@ -77,3 +361,12 @@ pub struct DumpStateResponse {
     pub funding_token_addr: Addr,
     pub version: String,
 }
+
+/// The subset of the dao-up-registry execute interface campaigns use
+/// to keep their listing up to date.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistryExecuteMsg {
+    /// Reports the campaign's current status and visibility.
+    CampaignUpdated { status: Status, hidden: bool },
+}
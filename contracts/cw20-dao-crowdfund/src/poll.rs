@@ -0,0 +1,80 @@
+use cosmwasm_std::{BlockInfo, Decimal, Fraction, Timestamp, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+
+/// What passing a poll does.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollKind {
+    /// Vetoes the DAO's claim on a milestone's funds.
+    VetoMilestone { milestone: u32 },
+    /// Cancels the campaign while it is open.
+    Cancel {},
+}
+
+/// Lets funding token holders cancel an open campaign without the
+/// DAO, for example if it has stopped responding.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CancellationConfig {
+    /// The share of outstanding funding tokens that must vote to
+    /// cancel the campaign.
+    pub quorum: Decimal,
+    /// Seconds a cancellation poll accepts votes for.
+    pub voting_period: u64,
+}
+
+impl CancellationConfig {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.quorum.is_zero() || self.quorum > Decimal::one() {
+            return Err(ContractError::Instantiation(
+                "cancellation quorum must be between zero and one".to_string(),
+            ));
+        }
+        if self.voting_period == 0 {
+            return Err(ContractError::Instantiation(
+                "cancellation voting period must be non-zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A vote by funding token holders. Holders vote for a poll by
+/// locking funding tokens in the campaign and may withdraw them once
+/// it has closed. A poll passes as soon as `threshold` of the
+/// outstanding funding tokens have voted for it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Poll {
+    pub id: u64,
+    pub kind: PollKind,
+    pub threshold: Decimal,
+    /// Votes are accepted until this time.
+    pub ends: Timestamp,
+    /// Funding tokens locked in favour of the poll.
+    pub votes: Uint128,
+    pub passed: bool,
+}
+
+impl Poll {
+    /// Whether the poll is accepting votes at `block`.
+    pub fn is_open(&self, block: &BlockInfo) -> bool {
+        !self.passed && block.time < self.ends
+    }
+
+    /// Whether `votes` meets the poll's threshold of `outstanding`
+    /// funding tokens.
+    pub fn reached_threshold(&self, outstanding: Uint128) -> bool {
+        !outstanding.is_zero()
+            && self.votes.full_mul(self.threshold.denominator())
+                >= outstanding.full_mul(self.threshold.numerator())
+    }
+}
+
+/// Funding tokens an address has locked in favour of a poll.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockedVote {
+    pub poll: u64,
+    pub tokens: Uint128,
+}
@@ -0,0 +1,59 @@
+use cosmwasm_std::{Addr, Uint128};
+use cw_utils::Expiration;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+use crate::error::ContractError;
+
+/// A presale phase during which only allowlisted addresses may fund
+/// the campaign.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Presale {
+    /// Hex encoded root of a Merkle tree whose leaves are the
+    /// sha256 hash of an address followed by the most it may
+    /// contribute during the presale, for example `juno1...1000000`.
+    pub merkle_root: String,
+    /// When the presale ends and anyone may fund the campaign.
+    pub ends: Expiration,
+}
+
+/// Validates that `merkle_root` is a hex encoded sha256 hash.
+pub fn validate_merkle_root(merkle_root: &str) -> Result<(), ContractError> {
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(merkle_root, &mut root_buf)
+        .map_err(|_| ContractError::InvalidMerkleRoot {})
+}
+
+/// Verifies that `proof` shows `address` may contribute up to
+/// `allocation` during the presale. Sibling hashes are sorted before
+/// being hashed together.
+pub fn verify_proof(
+    merkle_root: &str,
+    address: &Addr,
+    allocation: Uint128,
+    proof: &[String],
+) -> Result<(), ContractError> {
+    let mut hash: [u8; 32] = [0; 32];
+    hash.copy_from_slice(&sha2::Sha256::digest(
+        format!("{}{}", address, allocation).as_bytes(),
+    ));
+
+    for sibling in proof {
+        let mut sibling_buf: [u8; 32] = [0; 32];
+        hex::decode_to_slice(sibling, &mut sibling_buf)
+            .map_err(|_| ContractError::InvalidProof {})?;
+
+        let mut hashes = [hash, sibling_buf];
+        hashes.sort_unstable();
+        hash.copy_from_slice(&sha2::Sha256::digest(&hashes.concat()));
+    }
+
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(merkle_root, &mut root_buf)
+        .map_err(|_| ContractError::InvalidMerkleRoot {})?;
+    if root_buf != hash {
+        return Err(ContractError::InvalidProof {});
+    }
+    Ok(())
+}
@@ -0,0 +1,271 @@
+use std::convert::TryFrom;
+
+use cosmwasm_std::{Decimal, Fraction, Timestamp, Uint128, Uint256};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+
+/// A pricing tier. Contributions made while less than `up_to` has
+/// been raised receive `price` tokens per native token.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Tier {
+    pub up_to: Uint128,
+    pub price: Decimal,
+}
+
+/// Determines how many funding tokens are issued for a contribution
+/// based on how much has already been raised. Prices are in tokens
+/// per native token and never increase as the campaign raises funds
+/// so earlier backers receive at least as many tokens per native
+/// token as later ones.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PricingCurve {
+    /// Every contribution receives the same price. All governance
+    /// tokens sent by the DAO are sold once the funding goal is met.
+    Fixed {},
+    /// Prices change in steps as the amount raised crosses each
+    /// tier's `up_to`. Tiers must be ordered by `up_to` and the last
+    /// tier must cover the funding goal.
+    Tiers { tiers: Vec<Tier> },
+    /// The price falls linearly from `start_price` when nothing has
+    /// been raised to `end_price` at the funding goal.
+    Linear {
+        start_price: Decimal,
+        end_price: Decimal,
+    },
+    /// Prices follow a constant product curve between the
+    /// governance tokens sent by the DAO and `virtual_reserve` plus
+    /// the amount raised. A smaller virtual reserve rewards early
+    /// backers more heavily.
+    ConstantProduct { virtual_reserve: Uint128 },
+}
+
+/// An early-bird bonus. Contributions made within `until` seconds of
+/// the campaign opening receive `bonus` extra funding tokens per
+/// funding token issued.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BonusWindow {
+    pub until: u64,
+    pub bonus: Decimal,
+}
+
+/// Validates that bonus windows are ordered by when they end and
+/// that each grants a bonus.
+pub fn validate_bonus_windows(windows: &[BonusWindow]) -> Result<(), ContractError> {
+    if windows
+        .iter()
+        .any(|window| window.until == 0 || window.bonus.is_zero())
+    {
+        return Err(ContractError::Instantiation(
+            "bonus windows must have a non-zero duration and bonus".to_string(),
+        ));
+    }
+    if windows
+        .windows(2)
+        .any(|pair| pair[0].until >= pair[1].until)
+    {
+        return Err(ContractError::Instantiation(
+            "bonus windows must be ordered by when they end".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Gets the bonus for contributions made at `now` to a campaign that
+/// opened at `opened_at`.
+pub fn bonus_at(windows: &[BonusWindow], opened_at: Timestamp, now: Timestamp) -> Decimal {
+    windows
+        .iter()
+        .find(|window| now < opened_at.plus_seconds(window.until))
+        .map(|window| window.bonus)
+        .unwrap_or_else(Decimal::zero)
+}
+
+/// Gets the largest bonus any window grants.
+pub fn max_bonus(windows: &[BonusWindow]) -> Decimal {
+    windows
+        .iter()
+        .map(|window| window.bonus)
+        .max()
+        .unwrap_or_else(Decimal::zero)
+}
+
+/// Computes `amount * numerator / denominator` rounding down. The
+/// product is taken in 256 bits so no precision is lost to an
+/// intermediate price.
+pub fn mul_ratio(
+    amount: Uint128,
+    numerator: impl Into<u128>,
+    denominator: impl Into<u128>,
+) -> Result<Uint128, ContractError> {
+    let result = amount
+        .full_mul(numerator)
+        .checked_div(Uint256::from(denominator.into()))?;
+    Uint128::try_from(result).map_err(|_| ContractError::Overflow {})
+}
+
+/// Computes `amount * rate` rounding down. Unlike multiplying by a
+/// `Decimal` directly this returns an error instead of panicking if
+/// the result does not fit in a `Uint128`.
+pub fn mul_decimal(amount: Uint128, rate: Decimal) -> Result<Uint128, ContractError> {
+    mul_ratio(amount, rate.numerator(), rate.denominator())
+}
+
+/// Gets the number of governance tokens that may be sold along the
+/// pricing curve. The rest of `gov_tokens` are held back to pay the
+/// largest bonus on every token sold so that each funding token
+/// issued can always be exchanged for a governance token.
+pub fn sale_reserve(
+    windows: &[BonusWindow],
+    gov_tokens: Uint128,
+) -> Result<Uint128, ContractError> {
+    let one = Decimal::one().numerator();
+    let reserve = gov_tokens
+        .full_mul(one)
+        .checked_div(Uint256::from(one) + Uint256::from(max_bonus(windows).numerator()))?;
+    Uint128::try_from(reserve).map_err(|_| ContractError::Overflow {})
+}
+
+impl Default for PricingCurve {
+    fn default() -> Self {
+        PricingCurve::Fixed {}
+    }
+}
+
+impl PricingCurve {
+    /// Gets the funding tokens issued per unit of funds by a fixed
+    /// price curve selling `gov_tokens` for `funding_goal`, rounded
+    /// down. `None` for other curves or if the price is too large to
+    /// represent.
+    pub fn fixed_price(&self, funding_goal: Uint128, gov_tokens: Uint128) -> Option<Decimal> {
+        match self {
+            PricingCurve::Fixed {} => {
+                // `Decimal::from_ratio` panics if the price does not
+                // fit.
+                mul_ratio(gov_tokens, Decimal::one().numerator(), funding_goal).ok()?;
+                Some(Decimal::from_ratio(gov_tokens, funding_goal))
+            }
+            _ => None,
+        }
+    }
+
+    /// Validates the curve's parameters against the campaign's
+    /// funding goal.
+    pub fn validate(&self, funding_goal: Uint128) -> Result<(), ContractError> {
+        let invalid = |reason: &str| Err(ContractError::Instantiation(reason.to_string()));
+        match self {
+            PricingCurve::Fixed {} => Ok(()),
+            PricingCurve::Tiers { tiers } => {
+                let last = match tiers.last() {
+                    Some(last) => last,
+                    None => return invalid("pricing tiers can not be empty"),
+                };
+                if last.up_to < funding_goal {
+                    return invalid("pricing tiers must cover the funding goal");
+                }
+                if tiers.iter().any(|tier| tier.price.is_zero()) {
+                    return invalid("pricing tier prices must be non-zero");
+                }
+                if tiers
+                    .windows(2)
+                    .any(|pair| pair[0].up_to >= pair[1].up_to || pair[0].price < pair[1].price)
+                {
+                    return invalid(
+                        "pricing tiers must be ordered by amount raised and prices can not increase",
+                    );
+                }
+                Ok(())
+            }
+            PricingCurve::Linear {
+                start_price,
+                end_price,
+            } => {
+                if end_price.is_zero() || start_price < end_price {
+                    return invalid("linear pricing must start at or above a non-zero end price");
+                }
+                Ok(())
+            }
+            PricingCurve::ConstantProduct { virtual_reserve } => {
+                if virtual_reserve.is_zero() {
+                    return invalid("constant product pricing requires a non-zero virtual reserve");
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Computes the number of funding tokens issued for contributing
+    /// `amount` once `raised` has been raised. `gov_tokens` is the
+    /// number of governance tokens available for sale.
+    ///
+    /// The result is the exact area under the price curve between
+    /// `raised` and `raised + amount` rounded down, so splitting a
+    /// contribution never yields more tokens.
+    pub fn tokens_for(
+        &self,
+        funding_goal: Uint128,
+        gov_tokens: Uint128,
+        raised: Uint128,
+        amount: Uint128,
+    ) -> Result<Uint128, ContractError> {
+        let start = Uint256::from(raised);
+        let end = start + Uint256::from(amount);
+        let tokens = match self {
+            PricingCurve::Fixed {} => return mul_ratio(amount, gov_tokens, funding_goal),
+            PricingCurve::Tiers { tiers } => {
+                let mut area = Uint256::zero();
+                let mut lower = Uint256::zero();
+                for tier in tiers {
+                    let upper = Uint256::from(tier.up_to);
+                    let from = lower.max(start);
+                    let to = upper.min(end);
+                    if from < to {
+                        area = area.checked_add(
+                            Uint256::from(tier.price.numerator()).checked_mul(to - from)?,
+                        )?;
+                    }
+                    lower = upper;
+                }
+                area.checked_div(Uint256::from(Decimal::one().numerator()))?
+            }
+            PricingCurve::Linear {
+                start_price,
+                end_price,
+            } => {
+                // price(r) = start_price - (start_price - end_price) * r / goal
+                //
+                // Integrating over [start, end] and scaling by
+                // 2 * goal to stay in integers gives
+                // start_price * (end - start) * 2 * goal
+                //   - (start_price - end_price) * (end^2 - start^2).
+                let goal = Uint256::from(funding_goal);
+                let slope = Uint256::from(start_price.numerator() - end_price.numerator());
+                let area = Uint256::from(start_price.numerator())
+                    .checked_mul(end - start)?
+                    .checked_mul(goal)?
+                    .checked_mul(Uint256::from(2u128))?
+                    .checked_sub(
+                        slope.checked_mul(end.checked_mul(end)? - start.checked_mul(start)?)?,
+                    )?;
+                area.checked_div(
+                    goal.checked_mul(Uint256::from(2u128))?
+                        .checked_mul(Uint256::from(Decimal::one().numerator()))?,
+                )?
+            }
+            PricingCurve::ConstantProduct { virtual_reserve } => {
+                // The tokens left in the pool after `r` has been
+                // raised are gov_tokens * reserve / (reserve + r) so a
+                // contribution receives the difference between the
+                // two points.
+                let reserve = Uint256::from(*virtual_reserve);
+                Uint256::from(gov_tokens)
+                    .checked_mul(reserve)?
+                    .checked_mul(end - start)?
+                    .checked_div((reserve + start).checked_mul(reserve + end)?)?
+            }
+        };
+        Uint128::try_from(tokens).map_err(|_| ContractError::Overflow {})
+    }
+}
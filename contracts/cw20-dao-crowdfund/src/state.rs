@@ -1,8 +1,17 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Coin, Decimal, Timestamp, Uint128};
+use cw20::Denom;
+use cw_storage_plus::{Item, Map};
+use cw_utils::Expiration;
+
+use crate::dao::DaoType;
+use crate::milestones::{MilestoneConfig, MilestoneProgress};
+use crate::poll::{CancellationConfig, LockedVote, Poll};
+use crate::presale::Presale;
+use crate::pricing::{BonusWindow, PricingCurve};
+use crate::vesting::VestingConfig;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Campaign {
@@ -33,23 +42,30 @@ pub enum Status {
     /// The contract has received governance tokens from the DAO and
     /// is accepting funding.
     Open {
-        /// The token price in number of tokens per native token
-        /// (ex. uJuno).
-        token_price: Decimal,
+        /// The governance tokens sent by the DAO. Funding tokens are
+        /// priced at this many per `funding_goal` of the funding
+        /// asset and amounts are computed from the exact ratio.
         initial_gov_token_balance: Uint128,
     },
     /// The DAO has closed the campaign. Refunds are avaliable but no
     /// new funds may be added.
-    Cancelled {
-        token_price: Decimal,
-        initial_gov_token_balance: Uint128,
-    },
-    /// The campaign has met its funding goal. Tokens may now be
-    /// exchanged for governance tokens in the DAO.
-    Funded {
-        token_price: Decimal,
-        initial_gov_token_balance: Uint128,
-    },
+    Cancelled { initial_gov_token_balance: Uint128 },
+    /// The campaign has met its funding goal, or its minimum goal
+    /// and has been finalized. Tokens may now be exchanged for
+    /// governance tokens in the DAO.
+    Funded { initial_gov_token_balance: Uint128 },
+    /// The campaign's deadline passed before it met its minimum
+    /// goal. Refunds are avaliable but no new funds may be added.
+    Expired { initial_gov_token_balance: Uint128 },
+    /// The DAO aborted the campaign before it opened and any
+    /// governance tokens it held were returned. No actions can be
+    /// performed on the contract.
+    Aborted {},
+    /// The DAO settled the funded campaign once its funding tokens
+    /// were exchanged or its grace period passed. Governance tokens
+    /// and funds left over from rounding were swept to the DAO and
+    /// funding tokens can no longer be exchanged.
+    Settled {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -57,16 +73,124 @@ pub struct State {
     pub status: Status,
 
     pub dao_addr: Addr,
+    /// Determines how the DAO's governance token and treasury are
+    /// found.
+    pub dao_type: DaoType,
     pub fee_manager_addr: Addr,
+    /// The share of funds raised taken as a fee, fixed when the
+    /// campaign is created so the fee manager can not raise it
+    /// later.
+    pub fee: Decimal,
+    pub fee_receiver: Addr,
     pub creator: Addr,
+    /// Where the DAO's share of funds raised is sent. If empty it
+    /// is sent to the DAO's treasury.
+    pub beneficiaries: Vec<Beneficiary>,
 
+    /// The asset the campaign raises funds in.
+    pub funding_denom: Denom,
+    /// The maximum amount the campaign may raise. The campaign is
+    /// funded as soon as it is reached.
     pub funding_goal: Coin,
+    /// The amount the campaign must raise to be funded once its
+    /// deadline passes or the DAO finalizes it.
+    pub min_goal: Uint128,
+    /// Determines the number of funding tokens issued for each
+    /// contribution.
+    pub pricing: PricingCurve,
+    /// Early-bird bonuses paid on top of the pricing curve.
+    pub bonus_windows: Vec<BonusWindow>,
+    /// The smallest contribution accepted.
+    pub min_contribution: Option<Uint128>,
+    /// The most any one address may contribute, net of refunds.
+    pub max_contribution_per_address: Option<Uint128>,
+    /// An optional phase during which only allowlisted addresses may
+    /// fund the campaign.
+    pub presale: Option<Presale>,
+    /// If set, governance tokens received in exchange for funding
+    /// tokens vest on this schedule instead of being sent
+    /// immediately.
+    pub vesting: Option<VestingConfig>,
+    /// If set, funds raised are held by the campaign and released to
+    /// the DAO as it completes milestones.
+    pub milestones: Option<MilestoneConfig>,
+    /// If set, funding token holders may vote to cancel the campaign
+    /// while it is open.
+    pub cancellation: Option<CancellationConfig>,
+    /// The registry listing the campaign, if any.
+    pub registry: Option<Addr>,
     pub funds_raised: Coin,
+    /// Set for campaigns that accepted contributions before they
+    /// were recorded, i.e. those migrated from 0.3.0 once open. They
+    /// refund any funding token at the fixed token price and can not
+    /// process refunds in batches. Otherwise refunds are paid from
+    /// the ledger and the funds raised always equal what contributors
+    /// have paid less their refunds.
+    pub unrecorded_contributions: bool,
+    /// The point after which the campaign stops accepting funds. If
+    /// the minimum goal has not been met by then the campaign
+    /// expires.
+    pub deadline: Option<Expiration>,
+    /// When the campaign received governance tokens from the DAO
+    /// and opened.
+    pub opened_at: Option<Timestamp>,
+    /// When the campaign was funded. Vesting schedules start here.
+    pub funded_at: Option<Timestamp>,
 
     pub campaign_info: Campaign,
 }
 
+/// An address that receives a share of the funds raised in
+/// proportion to its weight.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Beneficiary {
+    pub address: Addr,
+    pub weight: u64,
+}
+
+/// A record of an address's dealings with the campaign. Funding
+/// tokens may be transferred so tokens burned or swapped by an
+/// address need not have been minted to it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Contribution {
+    /// Funds accepted from the address.
+    pub deposited: Uint128,
+    /// Funding tokens minted to the address.
+    pub minted: Uint128,
+    /// Funds refunded to the address.
+    pub refunded: Uint128,
+    /// Funding tokens refunded to the address. Includes tokens
+    /// refunded in a batch that were not returned.
+    pub burned: Uint128,
+    /// Funding tokens exchanged by the address for governance
+    /// tokens.
+    pub swapped: Uint128,
+}
+
+/// Governance tokens an address has received in exchange for funding
+/// tokens that are subject to the campaign's vesting schedule.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Vesting {
+    /// Governance tokens vesting for the address.
+    pub total: Uint128,
+    /// Of `total`, the number of tokens claimed.
+    pub claimed: Uint128,
+}
+
 pub const STATE: Item<State> = Item::new("state");
+pub const CONTRIBUTIONS: Map<&Addr, Contribution> = Map::new("contributions");
+pub const VESTING: Map<&Addr, Vesting> = Map::new("vesting");
+/// The last contributor whose refund was processed by the most
+/// recent batch. Unset once a pass over every contributor completes.
+pub const REFUND_CURSOR: Item<Addr> = Item::new("refund_cursor");
+pub const MILESTONE_PROGRESS: Item<MilestoneProgress> = Item::new("milestone_progress");
+
+/// The most recently opened poll.
+pub const POLL: Item<Poll> = Item::new("poll");
+pub const VOTES: Map<&Addr, LockedVote> = Map::new("votes");
+/// The total funding tokens locked in votes that have not been
+/// withdrawn.
+pub const VOTES_LOCKED: Item<Uint128> = Item::new("votes_locked");
 
 pub const GOV_TOKEN_ADDR: Item<Addr> = Item::new("gov_token_addr");
 pub const FUNDING_TOKEN_ADDR: Item<Addr> = Item::new("funding_token_addr");
@@ -1,16 +1,34 @@
+use cosmwasm_std::testing::{mock_env, MockApi, MockQuerier, MockStorage};
 use cosmwasm_std::{
-    to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Empty, Fraction, Uint128, WasmMsg,
+    to_binary, Addr, BankMsg, Binary, Coin, ContractResult, CosmosMsg, Decimal, Deps, DepsMut,
+    Empty, Env, Fraction, MessageInfo, QuerierWrapper, Response, StdError, StdResult, SystemResult,
+    Uint128, Uint256, WasmMsg,
 };
 use cw20::Cw20Coin;
 use cw3_dao::msg::GovTokenMsg;
 use cw_multi_test::{next_block, App, Contract, ContractWrapper, Executor};
-use cw_utils::Duration;
+use cw_storage_plus::Item;
+use cw_utils::{Duration, Expiration};
+
+use std::convert::TryFrom;
 
 use anyhow::Result as AnyResult;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::{
-    msg::{DumpStateResponse, ExecuteMsg, InstantiateMsg, QueryMsg},
-    state::{Campaign, Status},
+    dao::{CwCoreQueryMsg, DaoType, VotingModuleQueryMsg},
+    milestones::{Milestone, MilestoneConfig, VetoRefunds},
+    msg::{
+        BeneficiaryMsg, ContributorsResponse, DumpStateResponse, ExecuteMsg, InstantiateMsg,
+        MilestonesResponse, Payout, PresaleProof, QueryMsg, ReceiveMsg, SimulateFundResponse,
+        SimulateRefundResponse, SimulateSwapResponse, VestingResponse,
+    },
+    poll::{CancellationConfig, Poll, PollKind},
+    presale::Presale,
+    pricing::{mul_decimal, mul_ratio, sale_reserve, BonusWindow, PricingCurve, Tier},
+    state::{Campaign, Contribution, Status, STATE},
+    vesting::VestingConfig,
     ContractError,
 };
 
@@ -76,6 +94,77 @@ fn escrow_contract() -> Box<dyn Contract<Empty>> {
     Box::new(contract)
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct MockCoreInstantiateMsg {
+    voting_module: String,
+}
+
+const MOCK_VOTING_MODULE: Item<Addr> = Item::new("voting_module");
+
+/// A stand-in for a cw-core contract that executes any message sent
+/// to it.
+fn mock_cw_core_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        |_: DepsMut, _: Env, _: MessageInfo, msg: CosmosMsg| -> StdResult<Response> {
+            Ok(Response::default().add_message(msg))
+        },
+        |deps: DepsMut,
+         _: Env,
+         _: MessageInfo,
+         msg: MockCoreInstantiateMsg|
+         -> StdResult<Response> {
+            MOCK_VOTING_MODULE.save(deps.storage, &Addr::unchecked(msg.voting_module))?;
+            Ok(Response::default())
+        },
+        |deps: Deps, _: Env, msg: CwCoreQueryMsg| -> StdResult<Binary> {
+            match msg {
+                CwCoreQueryMsg::VotingModule {} => {
+                    to_binary(&MOCK_VOTING_MODULE.load(deps.storage)?)
+                }
+            }
+        },
+    );
+    Box::new(contract)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct MockVotingInstantiateMsg {
+    token_contract: String,
+    staking_contract: String,
+}
+
+const MOCK_TOKEN_CONTRACT: Item<Addr> = Item::new("token_contract");
+const MOCK_STAKING_CONTRACT: Item<Addr> = Item::new("staking_contract");
+
+/// A stand-in for a cw20 staked balance voting module.
+fn mock_voting_module_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        |_: DepsMut, _: Env, _: MessageInfo, _: Empty| -> StdResult<Response> {
+            Err(StdError::generic_err("not implemented"))
+        },
+        |deps: DepsMut,
+         _: Env,
+         _: MessageInfo,
+         msg: MockVotingInstantiateMsg|
+         -> StdResult<Response> {
+            MOCK_TOKEN_CONTRACT.save(deps.storage, &Addr::unchecked(msg.token_contract))?;
+            MOCK_STAKING_CONTRACT.save(deps.storage, &Addr::unchecked(msg.staking_contract))?;
+            Ok(Response::default())
+        },
+        |deps: Deps, _: Env, msg: VotingModuleQueryMsg| -> StdResult<Binary> {
+            match msg {
+                VotingModuleQueryMsg::TokenContract {} => {
+                    to_binary(&MOCK_TOKEN_CONTRACT.load(deps.storage)?)
+                }
+                VotingModuleQueryMsg::StakingContract {} => {
+                    to_binary(&MOCK_STAKING_CONTRACT.load(deps.storage)?)
+                }
+            }
+        },
+    );
+    Box::new(contract)
+}
+
 fn fee_manager_contract() -> Box<dyn Contract<Empty>> {
     let contract = ContractWrapper::new(
         fee_manager::contract::execute,
@@ -182,7 +271,9 @@ fn instantiate_msg_factory(
 ) -> InstantiateMsg {
     InstantiateMsg {
         dao_address: dao_addr.to_string(),
+        dao_type: None,
         fee_manager_address: fee_manager_addr.to_string(),
+        beneficiaries: None,
         cw20_code_id: cw20_id,
         funding_goal: Coin {
             denom: CHAIN_DENOM.to_string(),
@@ -190,6 +281,18 @@ fn instantiate_msg_factory(
         },
         funding_token_name: "Bong Launch".to_string(),
         funding_token_symbol: "LBONG".to_string(),
+        funding_cw20: None,
+        min_goal: None,
+        pricing: None,
+        bonus_windows: None,
+        min_contribution: None,
+        max_contribution_per_address: None,
+        presale: None,
+        vesting: None,
+        milestones: None,
+        cancellation: None,
+        registry: None,
+        deadline: None,
         campaign_info: Campaign {
             name: "Bong DAO".to_string(),
             description: "We're raising money to buy a bong!".to_string(),
@@ -396,6 +499,58 @@ fn close_escrow_from_dao(app: &mut App, dao_addr: Addr, escrow_addr: Addr) {
     app.update_block(next_block);
 }
 
+fn execute_from_dao(
+    app: &mut App,
+    dao_addr: Addr,
+    escrow_addr: Addr,
+    msg: &ExecuteMsg,
+    proposal_id: u64,
+) -> AnyResult<()> {
+    // Create the proposal.
+    let propose_msg = cw3_dao::msg::ExecuteMsg::Propose(cw3_dao::msg::ProposeMsg {
+        title: "Execute a message on the Bong DAO fundraising escrow contract".to_string(),
+        description: format!(
+            "execute a message on the DAO Up! campaign at ({})",
+            escrow_addr
+        ),
+        msgs: vec![cosmwasm_std::CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: escrow_addr.to_string(),
+            msg: to_binary(msg).unwrap(),
+            funds: vec![],
+        })],
+        latest: None,
+    });
+    app.execute_contract(
+        Addr::unchecked(CREATOR_ADDR),
+        dao_addr.clone(),
+        &propose_msg,
+        &[],
+    )
+    .unwrap();
+    app.update_block(next_block);
+
+    // Pass the proposal.
+    let yes_vote = cw3_dao::msg::ExecuteMsg::Vote(cw3_dao::msg::VoteMsg {
+        proposal_id,
+        vote: cw3::Vote::Yes,
+    });
+    app.execute_contract(
+        Addr::unchecked(CREATOR_ADDR),
+        dao_addr.clone(),
+        &yes_vote,
+        &[],
+    )
+    .unwrap();
+    app.update_block(next_block);
+
+    // Execute the proposal.
+    let execute = cw3_dao::msg::ExecuteMsg::Execute { proposal_id };
+    app.execute_contract(Addr::unchecked(CREATOR_ADDR), dao_addr, &execute, &[])?;
+    app.update_block(next_block);
+
+    Ok(())
+}
+
 #[test]
 #[should_panic]
 fn test_campaign_creation_with_invalid_cw20() {
@@ -815,10 +970,10 @@ fn test_campaign_creation() {
     assert_eq!(
         state.status,
         Status::Open {
-            token_price: Decimal::from_ratio(100_000_000 as u64, 100_000_000 as u64),
             initial_gov_token_balance: Uint128::from(gov_tokens),
         }
     );
+    assert_eq!(state.token_price, Some(Decimal::one()));
     assert_eq!(state.dao_addr, dao_addr);
     assert_eq!(state.funding_goal.amount, Uint128::from(100_000_000 as u64));
     assert_eq!(state.funds_raised.amount, Uint128::zero());
@@ -1031,8 +1186,6 @@ fn do_fund_refund(funding_goal: u64, gov_tokens: u64) {
 
     fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
 
-    let token_price = Decimal::from_ratio(gov_tokens, funding_goal);
-
     let state: DumpStateResponse = app
         .wrap()
         .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
@@ -1041,7 +1194,6 @@ fn do_fund_refund(funding_goal: u64, gov_tokens: u64) {
     assert_eq!(
         state.status,
         Status::Open {
-            token_price,
             initial_gov_token_balance: Uint128::from(gov_tokens)
         }
     );
@@ -1063,7 +1215,7 @@ fn do_fund_refund(funding_goal: u64, gov_tokens: u64) {
             .unwrap_err()
             .downcast()
             .unwrap();
-        assert_eq!(err, ContractError::SmallContribution { token_price });
+        assert_eq!(err, ContractError::SmallContribution {});
     }
 
     let backer_contribution = Uint128::from(funding_goal / (backers.len() as u64));
@@ -1091,7 +1243,7 @@ fn do_fund_refund(funding_goal: u64, gov_tokens: u64) {
                 },
             )
             .unwrap();
-        let expected_balance = backer_contribution * token_price;
+        let expected_balance = backer_contribution.multiply_ratio(gov_tokens, funding_goal);
         assert_eq!(balance.balance, expected_balance,);
 
         if gov_tokens > funding_goal {
@@ -1111,7 +1263,7 @@ fn do_fund_refund(funding_goal: u64, gov_tokens: u64) {
                 .unwrap_err()
                 .downcast()
                 .unwrap();
-            assert_eq!(err, ContractError::SmallRefund { token_price });
+            assert_eq!(err, ContractError::SmallRefund {});
         }
 
         // Return all the backers tokens.
@@ -1127,31 +1279,20 @@ fn do_fund_refund(funding_goal: u64, gov_tokens: u64) {
         )
         .unwrap();
 
-        // Check that the funds were returned. Note that the final
-        // balance here may be slightly less than the contributed
-        // amount if interesting token prices cause rounding down.
+        // Check that the funds were returned. Refunds unwind at the
+        // price paid so no rounding occurs when all tokens are
+        // returned.
         let backer_balance = app.wrap().query_balance(backer, CHAIN_DENOM).unwrap();
-        assert_eq!(
-            backer_balance.amount,
-            backer_initial_balance - backer_contribution
-                + expected_balance * token_price.inv().unwrap()
-        );
+        assert_eq!(backer_balance.amount, backer_initial_balance);
     }
 
     let escrow_balance = app
         .wrap()
         .query_balance(escrow_addr.clone(), CHAIN_DENOM)
         .unwrap();
+    assert_eq!(escrow_balance.amount, Uint128::zero());
 
-    // It is possible that after a number of refunds the escrow
-    // account has a non-zero balance as token_price *
-    // token_price.inv() may round down.
-    let expected_escrow_balance = Uint128::from((backers.len() / 2) as u64)
-        * (backer_contribution - backer_contribution * token_price * token_price.inv().unwrap());
-
-    assert_eq!(escrow_balance.amount, expected_escrow_balance);
-
-    let mut sent = expected_escrow_balance;
+    let mut sent = Uint128::zero();
     // Fully fund the contract.
     for backer in backers.iter() {
         app.execute_contract(
@@ -1181,7 +1322,6 @@ fn do_fund_refund(funding_goal: u64, gov_tokens: u64) {
     assert_eq!(
         state.status,
         Status::Funded {
-            token_price,
             initial_gov_token_balance: Uint128::from(gov_tokens)
         }
     );
@@ -1324,7 +1464,6 @@ fn test_campaign_completion() {
     assert_eq!(
         state.status,
         Status::Funded {
-            token_price,
             initial_gov_token_balance: Uint128::from(gov_tokens)
         }
     );
@@ -1505,7 +1644,6 @@ fn test_campaign_close() {
     assert_eq!(
         state.status,
         Status::Cancelled {
-            token_price,
             initial_gov_token_balance: Uint128::from(gov_tokens)
         }
     );
@@ -1540,3 +1678,4541 @@ fn test_campaign_close() {
     )
     .unwrap();
 }
+
+#[test]
+fn test_campaign_deadline() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 100_000_000;
+    let backer_contribution = Uint128::from(10_000_000 as u64);
+    let backer_initial_balance = Uint128::from(1_000_000_000 as u64);
+
+    let mut app = App::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked("backer_1"),
+                vec![Coin {
+                    denom: CHAIN_DENOM.to_string(),
+                    amount: backer_initial_balance,
+                }],
+            )
+            .unwrap();
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    // Campaigns can not be created with a deadline in the past.
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr.clone(),
+        cw20_id,
+        funding_goal,
+        true,
+    );
+    instantiate.deadline = Some(Expiration::AtHeight(app.block_info().height));
+    let err: ContractError = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::Instantiation(_)));
+
+    instantiate.deadline = Some(Expiration::AtHeight(app.block_info().height + 10));
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: backer_contribution,
+        }],
+    )
+    .unwrap();
+
+    // The campaign can not be finalized before its deadline.
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::Finalize {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidFinalize {});
+
+    app.update_block(|block| block.height += 10);
+
+    // The campaign expires without anyone updating it.
+    let token_price = Decimal::from_ratio(gov_tokens, funding_goal);
+    let status: Status = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(
+        status,
+        Status::Expired {
+            initial_gov_token_balance: Uint128::from(gov_tokens),
+        }
+    );
+
+    // Funding is no longer possible.
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: backer_contribution,
+            }],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Expired {});
+
+    // Anyone may finalize the campaign which returns the governance
+    // tokens to the DAO.
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Finalize {},
+        &[],
+    )
+    .unwrap();
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    let dao_gov_balance: cw20::BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            state.gov_token_addr.clone(),
+            &cw20::Cw20QueryMsg::Balance {
+                address: dao_addr.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        dao_gov_balance.balance,
+        Uint128::from(100_000_000_000 as u64)
+    );
+
+    // Finalizing twice is not possible.
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::Finalize {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidFinalize {});
+
+    // Refunds are still avaliable.
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        state.funding_token_addr,
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: backer_contribution * token_price,
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    let backer_balance = app.wrap().query_balance("backer_1", CHAIN_DENOM).unwrap();
+    assert_eq!(backer_balance.amount, backer_initial_balance);
+}
+
+#[test]
+fn test_campaign_registry_rejects_callback() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 100_000_000;
+    let backer_contribution = Uint128::from(10_000_000 as u64);
+    let backer_initial_balance = Uint128::from(1_000_000_000 as u64);
+
+    let mut app = App::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked("backer_1"),
+                vec![Coin {
+                    denom: CHAIN_DENOM.to_string(),
+                    amount: backer_initial_balance,
+                }],
+            )
+            .unwrap();
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    // Only the contract instantiating the campaign may be its
+    // registry.
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr.clone(),
+        cw20_id,
+        funding_goal,
+        true,
+    );
+    instantiate.deadline = Some(Expiration::AtHeight(app.block_info().height + 10));
+    instantiate.registry = Some(fee_manager_addr.to_string());
+    let err: ContractError = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::Instantiation(_)));
+
+    // The fee manager does not understand registry callbacks so
+    // rejects every one of them.
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            fee_manager_addr.clone(),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr, escrow_addr.clone(), gov_tokens);
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: backer_contribution,
+        }],
+    )
+    .unwrap();
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert_eq!(state.registry, Some(fee_manager_addr));
+    assert!(matches!(state.status, Status::Open { .. }));
+
+    // Refunds after the deadline expire the campaign and still go
+    // through.
+    app.update_block(|block| block.height += 10);
+    let tokens: cw20::BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            state.funding_token_addr.clone(),
+            &cw20::Cw20QueryMsg::Balance {
+                address: "backer_1".to_string(),
+            },
+        )
+        .unwrap();
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        state.funding_token_addr,
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: tokens.balance,
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let balance = app
+        .wrap()
+        .query_balance("backer_1", CHAIN_DENOM)
+        .unwrap()
+        .amount;
+    assert_eq!(balance, backer_initial_balance);
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr, &QueryMsg::DumpState {})
+        .unwrap();
+    assert!(matches!(state.status, Status::Expired { .. }));
+}
+
+#[test]
+fn test_campaign_min_goal() {
+    let funding_goal = 100_000_000;
+    let min_goal = 50_000_000;
+    let gov_tokens = 100_000_000;
+    let backer_contribution = Uint128::from(30_000_000 as u64);
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    // The minimum goal may not exceed the funding goal.
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr.clone(),
+        cw20_id,
+        funding_goal,
+        true,
+    );
+    instantiate.min_goal = Some(Uint128::from(funding_goal + 1));
+    let err: ContractError = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::Instantiation(_)));
+
+    instantiate.min_goal = Some(Uint128::from(min_goal));
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: backer_contribution,
+        }],
+    )
+    .unwrap();
+
+    // The DAO may not finalize the campaign before it has met its
+    // minimum goal.
+    let err: ContractError = execute_from_dao(
+        &mut app,
+        dao_addr.clone(),
+        escrow_addr.clone(),
+        &ExecuteMsg::Finalize {},
+        2,
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert_eq!(err, ContractError::InvalidFinalize {});
+
+    app.execute_contract(
+        Addr::unchecked("backer_2"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: backer_contribution,
+        }],
+    )
+    .unwrap();
+
+    // Only the DAO may finalize a campaign before its deadline.
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::Finalize {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidFinalize {});
+
+    execute_from_dao(
+        &mut app,
+        dao_addr.clone(),
+        escrow_addr.clone(),
+        &ExecuteMsg::Finalize {},
+        3,
+    )
+    .unwrap();
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert_eq!(
+        state.status,
+        Status::Funded {
+            initial_gov_token_balance: Uint128::from(gov_tokens),
+        }
+    );
+    assert_eq!(
+        state.funds_raised.amount,
+        backer_contribution * Uint128::from(2u64)
+    );
+
+    // Governance tokens that were not sold are returned to the DAO.
+    let dao_gov_balance: cw20::BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            state.gov_token_addr.clone(),
+            &cw20::Cw20QueryMsg::Balance {
+                address: dao_addr.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        dao_gov_balance.balance,
+        Uint128::from(100_000_000_000 - gov_tokens + (gov_tokens - 60_000_000))
+    );
+
+    // No more funds may be added.
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: backer_contribution,
+            }],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NotOpen {});
+
+    // Backers may swap for governance tokens and the DAO receives
+    // the funds raised less fees.
+    for backer in ["backer_1", "backer_2"] {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            state.funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: backer_contribution,
+                msg: to_binary("").unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                state.gov_token_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: backer.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, backer_contribution);
+    }
+
+    let expected_fee = backer_contribution * Decimal::percent(3);
+    let dao_balance = app
+        .wrap()
+        .query_balance(dao_addr.clone(), CHAIN_DENOM)
+        .unwrap();
+    assert_eq!(
+        dao_balance.amount,
+        (backer_contribution - expected_fee) * Uint128::from(2u64)
+    );
+}
+
+#[test]
+fn test_campaign_cw20_funding() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+    let token_price = Decimal::from_ratio(gov_tokens, funding_goal);
+    let backer_contribution = Uint128::from(50_000_000 as u64);
+
+    let mut app = App::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked("backer_1"),
+                vec![Coin {
+                    denom: CHAIN_DENOM.to_string(),
+                    amount: Uint128::from(1_000_000_000 as u64),
+                }],
+            )
+            .unwrap();
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    // A stablecoin to raise funds in.
+    let stable_addr = app
+        .instantiate_contract(
+            cw20_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Stable".to_string(),
+                symbol: "STBL".to_string(),
+                decimals: 6,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: "backer_1".to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    },
+                    Cw20Coin {
+                        address: "backer_2".to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    },
+                ],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "Stable",
+            None,
+        )
+        .unwrap();
+
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr,
+        cw20_id,
+        funding_goal,
+        true,
+    );
+    instantiate.funding_goal.denom = stable_addr.to_string();
+
+    // The funding goal denom must match the funding cw20.
+    instantiate.funding_cw20 = Some(dao_addr.to_string());
+    let err: ContractError = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::Instantiation(_)));
+
+    instantiate.funding_cw20 = Some(stable_addr.to_string());
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert_eq!(state.funding_denom, cw20::Denom::Cw20(stable_addr.clone()));
+
+    // Native funds are not accepted.
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: backer_contribution,
+            }],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::InvalidFundingAsset {}));
+
+    let stable_balance = |app: &App, address: &str| -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                stable_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+
+    // Fund and refund.
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        stable_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: backer_contribution,
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        stable_balance(&app, escrow_addr.as_str()),
+        backer_contribution
+    );
+
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        state.funding_token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: backer_contribution * token_price,
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(stable_balance(&app, escrow_addr.as_str()), Uint128::zero());
+    assert_eq!(
+        stable_balance(&app, "backer_1"),
+        Uint128::from(1_000_000_000 as u64)
+    );
+
+    // Fund the campaign to completion.
+    for backer in ["backer_1", "backer_2"] {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            stable_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: backer_contribution,
+                msg: to_binary("").unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert_eq!(
+        state.status,
+        Status::Funded {
+            initial_gov_token_balance: Uint128::from(gov_tokens)
+        }
+    );
+
+    // Swap for gov tokens.
+    for backer in ["backer_1", "backer_2"] {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            state.funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: backer_contribution * token_price,
+                msg: to_binary("").unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // The DAO and fee receiver are paid in the funding cw20.
+    let expected_fee = Uint128::from(funding_goal) * Decimal::percent(3);
+    let expected_dao = Uint128::from(funding_goal) - expected_fee;
+    assert_eq!(stable_balance(&app, dao_addr.as_str()), expected_dao);
+    assert_eq!(stable_balance(&app, DAO_UP_ADDR), expected_fee);
+    assert_eq!(stable_balance(&app, escrow_addr.as_str()), Uint128::zero());
+}
+
+#[test]
+fn test_campaign_partial_fill() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+    let token_price = Decimal::from_ratio(gov_tokens, funding_goal);
+    let backer_initial_balance = Uint128::from(1_000_000_000 as u64);
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: backer_initial_balance,
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+    let escrow_addr = instantiate_escrow(
+        &mut app,
+        dao_addr.clone(),
+        fee_manager_addr,
+        escrow_id,
+        cw20_id,
+        funding_goal,
+        true,
+    )
+    .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr, escrow_addr.clone(), gov_tokens);
+
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: Uint128::from(80_000_000 as u64),
+        }],
+    )
+    .unwrap();
+
+    // Contributing past the funding goal only accepts what is
+    // needed and refunds the rest.
+    app.execute_contract(
+        Addr::unchecked("backer_2"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: Uint128::from(50_000_000 as u64),
+        }],
+    )
+    .unwrap();
+
+    let accepted = Uint128::from(20_000_000 as u64);
+    let balance = app.wrap().query_balance("backer_2", CHAIN_DENOM).unwrap();
+    assert_eq!(balance.amount, backer_initial_balance - accepted);
+
+    let escrow_balance = app
+        .wrap()
+        .query_balance(escrow_addr.clone(), CHAIN_DENOM)
+        .unwrap();
+    assert_eq!(escrow_balance.amount, Uint128::from(funding_goal));
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert_eq!(
+        state.status,
+        Status::Funded {
+            initial_gov_token_balance: Uint128::from(gov_tokens)
+        }
+    );
+    assert_eq!(state.funds_raised.amount, Uint128::from(funding_goal));
+
+    // Funding tokens are only minted for the accepted amount.
+    let balance: cw20::BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            state.funding_token_addr,
+            &cw20::Cw20QueryMsg::Balance {
+                address: "backer_2".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance.balance, accepted * token_price);
+
+    // Funded campaigns accept no further contributions.
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr,
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(1_000_000 as u64),
+            }],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::NotOpen {}));
+}
+
+#[test]
+fn test_contributor_ledger() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+    let escrow_addr = instantiate_escrow(
+        &mut app,
+        dao_addr.clone(),
+        fee_manager_addr,
+        escrow_id,
+        cw20_id,
+        funding_goal,
+        true,
+    )
+    .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr, escrow_addr.clone(), gov_tokens);
+
+    let funding_token_addr: Addr = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::FundingTokenAddr {})
+        .unwrap();
+
+    let fund = |app: &mut App, backer: &str, amount: u64| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+        .unwrap();
+    };
+    let return_tokens = |app: &mut App, backer: &str, amount: u64| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: Uint128::from(amount),
+                msg: to_binary("").unwrap(),
+            },
+            &[],
+        )
+    };
+    let contribution = |app: &App, address: &str| -> Contribution {
+        app.wrap()
+            .query_wasm_smart(
+                escrow_addr.clone(),
+                &QueryMsg::Contribution {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+    };
+
+    fund(&mut app, "backer_1", 30_000_000);
+    fund(&mut app, "backer_2", 20_000_000);
+    return_tokens(&mut app, "backer_1", 5_000_000).unwrap();
+
+    // Refunds are only paid for tokens the ledger says the sender
+    // holds so tokens passed on can not be refunded.
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        funding_token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Transfer {
+            recipient: "backer_3".to_string(),
+            amount: Uint128::from(2_000_000 as u64),
+        },
+        &[],
+    )
+    .unwrap();
+    let err: ContractError = return_tokens(&mut app, "backer_3", 2_000_000)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::RefundExceedsContribution {});
+
+    assert_eq!(
+        contribution(&app, "backer_1"),
+        Contribution {
+            deposited: Uint128::from(30_000_000 as u64),
+            minted: Uint128::from(15_000_000 as u64),
+            refunded: Uint128::from(10_000_000 as u64),
+            burned: Uint128::from(5_000_000 as u64),
+            swapped: Uint128::zero(),
+        }
+    );
+    assert_eq!(contribution(&app, "backer_3"), Contribution::default());
+
+    let page: ContributorsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            escrow_addr.clone(),
+            &QueryMsg::Contributors {
+                start_after: None,
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+    let addresses: Vec<_> = page
+        .contributors
+        .iter()
+        .map(|c| c.address.to_string())
+        .collect();
+    assert_eq!(addresses, vec!["backer_1", "backer_2"]);
+
+    let page: ContributorsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            escrow_addr.clone(),
+            &QueryMsg::Contributors {
+                start_after: Some("backer_1".to_string()),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(page.contributors.len(), 1);
+    assert_eq!(page.contributors[0].address, Addr::unchecked("backer_2"));
+    assert_eq!(
+        page.contributors[0].contribution,
+        contribution(&app, "backer_2")
+    );
+
+    // Only the accepted part of a partially filled contribution is
+    // recorded.
+    fund(&mut app, "backer_2", 80_000_000);
+    return_tokens(&mut app, "backer_2", 40_000_000).unwrap();
+    assert_eq!(
+        contribution(&app, "backer_2"),
+        Contribution {
+            deposited: Uint128::from(80_000_000 as u64),
+            minted: Uint128::from(40_000_000 as u64),
+            swapped: Uint128::from(40_000_000 as u64),
+            ..Contribution::default()
+        }
+    );
+}
+
+#[test]
+fn test_simulation_queries() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 30_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+    let escrow_addr = instantiate_escrow(
+        &mut app,
+        dao_addr.clone(),
+        fee_manager_addr,
+        escrow_id,
+        cw20_id,
+        funding_goal,
+        true,
+    )
+    .unwrap();
+
+    let query_err = |app: &App, msg: &QueryMsg| -> String {
+        app.wrap()
+            .query_wasm_smart::<Binary>(escrow_addr.clone(), msg)
+            .unwrap_err()
+            .to_string()
+    };
+
+    // Simulations fail the same way execution would.
+    let amount = Uint128::from(33_000_000 as u64);
+    let err = query_err(
+        &app,
+        &QueryMsg::SimulateFund {
+            address: "backer_1".to_string(),
+            amount,
+            presale: None,
+        },
+    );
+    assert!(err.contains(&ContractError::NotOpen {}.to_string()));
+
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    let token_balance = |app: &App, address: &str| -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                state.funding_token_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+    let native_balance = |app: &App, address: &str| -> Uint128 {
+        app.wrap()
+            .query_balance(address, CHAIN_DENOM)
+            .unwrap()
+            .amount
+    };
+
+    let simulated: SimulateFundResponse = app
+        .wrap()
+        .query_wasm_smart(
+            escrow_addr.clone(),
+            &QueryMsg::SimulateFund {
+                address: "backer_1".to_string(),
+                amount,
+                presale: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        simulated,
+        SimulateFundResponse {
+            accepted: amount,
+            refunded: Uint128::zero(),
+            minted: Uint128::from(9_900_000 as u64),
+            bonus: Uint128::zero(),
+        }
+    );
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount,
+        }],
+    )
+    .unwrap();
+    assert_eq!(token_balance(&app, "backer_1"), simulated.minted);
+
+    let err = query_err(
+        &app,
+        &QueryMsg::SimulateRefund {
+            address: "backer_1".to_string(),
+            tokens: Uint128::zero(),
+        },
+    );
+    assert!(err.contains("Refund token amount too small"));
+    let err = query_err(&app, &QueryMsg::SimulateSwap { tokens: amount });
+    assert!(err.contains(&ContractError::NotFunded {}.to_string()));
+
+    // Refunds round down.
+    let tokens = Uint128::from(1_000_001 as u64);
+    let simulated: SimulateRefundResponse = app
+        .wrap()
+        .query_wasm_smart(
+            escrow_addr.clone(),
+            &QueryMsg::SimulateRefund {
+                address: "backer_1".to_string(),
+                tokens,
+            },
+        )
+        .unwrap();
+    assert_eq!(simulated.burned, tokens);
+    let before = native_balance(&app, "backer_1");
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        state.funding_token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: tokens,
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        native_balance(&app, "backer_1"),
+        before + simulated.refunded
+    );
+
+    // Contributions past the goal are partially accepted.
+    let amount = Uint128::from(80_000_000 as u64);
+    let simulated: SimulateFundResponse = app
+        .wrap()
+        .query_wasm_smart(
+            escrow_addr.clone(),
+            &QueryMsg::SimulateFund {
+                address: "backer_2".to_string(),
+                amount,
+                presale: None,
+            },
+        )
+        .unwrap();
+    let before = native_balance(&app, "backer_2");
+    app.execute_contract(
+        Addr::unchecked("backer_2"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount,
+        }],
+    )
+    .unwrap();
+    assert_eq!(simulated.accepted + simulated.refunded, amount);
+    assert_eq!(
+        native_balance(&app, "backer_2"),
+        before - simulated.accepted
+    );
+    assert_eq!(token_balance(&app, "backer_2"), simulated.minted);
+
+    let err = query_err(
+        &app,
+        &QueryMsg::SimulateRefund {
+            address: "backer_2".to_string(),
+            tokens,
+        },
+    );
+    assert!(err.contains(&ContractError::NoRefunds {}.to_string()));
+
+    // Swaps pay the DAO and fee receiver what was simulated.
+    let tokens = token_balance(&app, "backer_2");
+    let simulated: SimulateSwapResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::SimulateSwap { tokens })
+        .unwrap();
+    assert_eq!(simulated.gov_tokens, tokens);
+    assert!(!simulated.fee.is_zero());
+    app.execute_contract(
+        Addr::unchecked("backer_2"),
+        state.funding_token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: tokens,
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        native_balance(&app, dao_addr.as_str()),
+        simulated.dao_amount
+    );
+    assert_eq!(native_balance(&app, DAO_UP_ADDR), simulated.fee);
+}
+
+#[test]
+fn test_pricing_curve_math() {
+    let goal = Uint128::from(100u128);
+    let gov = Uint128::from(1000u128);
+    let tokens = |curve: &PricingCurve, raised: u128, amount: u128| {
+        curve
+            .tokens_for(goal, gov, Uint128::from(raised), Uint128::from(amount))
+            .unwrap()
+            .u128()
+    };
+
+    let fixed = PricingCurve::Fixed {};
+    assert_eq!(tokens(&fixed, 0, 100), 1000);
+    assert_eq!(tokens(&fixed, 50, 5), 50);
+
+    let linear = PricingCurve::Linear {
+        start_price: Decimal::from_ratio(2u128, 1u128),
+        end_price: Decimal::one(),
+    };
+    linear.validate(goal).unwrap();
+    assert_eq!(tokens(&linear, 0, 100), 150);
+    assert_eq!(tokens(&linear, 0, 50), 87);
+    assert_eq!(tokens(&linear, 50, 50), 62);
+
+    let constant_product = PricingCurve::ConstantProduct {
+        virtual_reserve: Uint128::from(100u128),
+    };
+    constant_product.validate(goal).unwrap();
+    assert_eq!(tokens(&constant_product, 0, 100), 500);
+    assert_eq!(tokens(&constant_product, 0, 50), 333);
+    assert_eq!(tokens(&constant_product, 50, 50), 166);
+
+    let tiers = PricingCurve::Tiers {
+        tiers: vec![
+            Tier {
+                up_to: Uint128::from(50u128),
+                price: Decimal::percent(300),
+            },
+            Tier {
+                up_to: Uint128::from(100u128),
+                price: Decimal::percent(150),
+            },
+        ],
+    };
+    tiers.validate(goal).unwrap();
+    assert_eq!(tokens(&tiers, 0, 100), 225);
+    assert_eq!(tokens(&tiers, 40, 20), 45);
+
+    // Prices may not increase and tiers must cover the goal.
+    let invalid = [
+        PricingCurve::Tiers { tiers: vec![] },
+        PricingCurve::Tiers {
+            tiers: vec![Tier {
+                up_to: Uint128::from(99u128),
+                price: Decimal::one(),
+            }],
+        },
+        PricingCurve::Tiers {
+            tiers: vec![
+                Tier {
+                    up_to: Uint128::from(50u128),
+                    price: Decimal::one(),
+                },
+                Tier {
+                    up_to: Uint128::from(100u128),
+                    price: Decimal::percent(200),
+                },
+            ],
+        },
+        PricingCurve::Linear {
+            start_price: Decimal::one(),
+            end_price: Decimal::percent(200),
+        },
+        PricingCurve::ConstantProduct {
+            virtual_reserve: Uint128::zero(),
+        },
+    ];
+    for curve in invalid.iter() {
+        assert!(matches!(
+            curve.validate(goal),
+            Err(ContractError::Instantiation(_))
+        ));
+    }
+}
+
+#[test]
+fn test_campaign_tiered_pricing() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 75_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    // Early backers receive one token per native token and later
+    // ones half a token.
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr,
+        cw20_id,
+        funding_goal,
+        true,
+    );
+    instantiate.pricing = Some(PricingCurve::Tiers {
+        tiers: vec![
+            Tier {
+                up_to: Uint128::from(50_000_000 as u64),
+                price: Decimal::one(),
+            },
+            Tier {
+                up_to: Uint128::from(funding_goal),
+                price: Decimal::percent(50),
+            },
+        ],
+    });
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    // Tiered campaigns have no single price.
+    assert_eq!(state.token_price, None);
+
+    let fund = |app: &mut App, backer: &str, amount: u64| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+    };
+    let return_tokens = |app: &mut App, backer: &str, amount: u64| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            state.funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: Uint128::from(amount),
+                msg: to_binary("").unwrap(),
+            },
+            &[],
+        )
+    };
+    let token_balance = |app: &App, address: &str| -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                state.funding_token_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+    let native_balance = |app: &App, address: &str| -> Uint128 {
+        app.wrap()
+            .query_balance(address, CHAIN_DENOM)
+            .unwrap()
+            .amount
+    };
+
+    fund(&mut app, "backer_1", 40_000_000).unwrap();
+    assert_eq!(
+        token_balance(&app, "backer_1"),
+        Uint128::from(40_000_000 as u64)
+    );
+
+    // Contributions spanning tiers are priced in each.
+    fund(&mut app, "backer_2", 20_000_000).unwrap();
+    assert_eq!(
+        token_balance(&app, "backer_2"),
+        Uint128::from(15_000_000 as u64)
+    );
+
+    // Refunds unwind at the price paid.
+    return_tokens(&mut app, "backer_2", 15_000_000).unwrap();
+    assert_eq!(
+        native_balance(&app, "backer_2"),
+        Uint128::from(1_000_000_000 as u64)
+    );
+    return_tokens(&mut app, "backer_1", 10_000_000).unwrap();
+    assert_eq!(
+        native_balance(&app, "backer_1"),
+        Uint128::from(970_000_000 as u64)
+    );
+
+    // Tokens not issued to the sender can not be refunded.
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        state.funding_token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Transfer {
+            recipient: "backer_3".to_string(),
+            amount: Uint128::from(1_000_000 as u64),
+        },
+        &[],
+    )
+    .unwrap();
+    let err: ContractError = return_tokens(&mut app, "backer_3", 1_000_000)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::RefundExceedsContribution {});
+
+    fund(&mut app, "backer_2", 70_000_000).unwrap();
+    assert_eq!(
+        token_balance(&app, "backer_2"),
+        Uint128::from(45_000_000 as u64)
+    );
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert!(matches!(state.status, Status::Funded { .. }));
+    assert_eq!(
+        state.funding_token_info.total_supply,
+        Uint128::from(gov_tokens)
+    );
+
+    // Swaps pay out the funds raised per token issued.
+    return_tokens(&mut app, "backer_2", 45_000_000).unwrap();
+    let payout = Uint128::from(60_000_000 as u64);
+    let fee = payout * Decimal::percent(3);
+    assert_eq!(native_balance(&app, dao_addr.as_str()), payout - fee);
+}
+
+#[test]
+fn test_campaign_bonus_windows() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 60_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr,
+        cw20_id,
+        funding_goal,
+        true,
+    );
+
+    // Bonus windows must be ordered.
+    instantiate.bonus_windows = Some(vec![
+        BonusWindow {
+            until: 172_800,
+            bonus: Decimal::percent(10),
+        },
+        BonusWindow {
+            until: 86_400,
+            bonus: Decimal::percent(20),
+        },
+    ]);
+    let err: ContractError = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::Instantiation(_)));
+
+    // 20% more tokens during the first day.
+    instantiate.bonus_windows = Some(vec![BonusWindow {
+        until: 86_400,
+        bonus: Decimal::percent(20),
+    }]);
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert!(state.opened_at.is_some());
+
+    let fund = |app: &mut App, backer: &str, amount: u64| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+        .unwrap();
+    };
+    let token_balance = |app: &App, address: &str| -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                state.funding_token_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+
+    // A sixth of the governance tokens are held back to pay bonuses
+    // so tokens sell at half a token per native token.
+    let simulated: SimulateFundResponse = app
+        .wrap()
+        .query_wasm_smart(
+            escrow_addr.clone(),
+            &QueryMsg::SimulateFund {
+                address: "backer_1".to_string(),
+                amount: Uint128::from(40_000_000 as u64),
+                presale: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(simulated.minted, Uint128::from(24_000_000 as u64));
+    assert_eq!(simulated.bonus, Uint128::from(4_000_000 as u64));
+    fund(&mut app, "backer_1", 40_000_000);
+    assert_eq!(token_balance(&app, "backer_1"), simulated.minted);
+
+    // No bonus once the window has passed.
+    app.update_block(|block| block.time = block.time.plus_seconds(86_400));
+    fund(&mut app, "backer_2", 60_000_000);
+    assert_eq!(
+        token_balance(&app, "backer_2"),
+        Uint128::from(30_000_000 as u64)
+    );
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert!(matches!(state.status, Status::Funded { .. }));
+
+    // Unused bonus tokens are returned to the DAO and every funding
+    // token can still be exchanged for a governance token.
+    let gov_balance = |app: &App, address: &str| -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                state.gov_token_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+    assert_eq!(
+        gov_balance(&app, escrow_addr.as_str()),
+        state.funding_token_info.total_supply
+    );
+    assert_eq!(
+        gov_balance(&app, dao_addr.as_str()),
+        Uint128::from(100_000_000_000 - gov_tokens + 6_000_000 as u64)
+    );
+
+    for backer in ["backer_1", "backer_2"] {
+        let tokens = token_balance(&app, backer);
+        app.execute_contract(
+            Addr::unchecked(backer),
+            state.funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: tokens,
+                msg: to_binary("").unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(gov_balance(&app, backer), tokens);
+    }
+    assert_eq!(gov_balance(&app, escrow_addr.as_str()), Uint128::zero());
+}
+
+#[test]
+fn test_campaign_contribution_limits() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2", "backer_3"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr,
+        cw20_id,
+        funding_goal,
+        true,
+    );
+
+    // The maximum may not be below the minimum.
+    instantiate.min_contribution = Some(Uint128::from(1_000_000 as u64));
+    instantiate.max_contribution_per_address = Some(Uint128::from(999_999 as u64));
+    let err: ContractError = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::Instantiation(_)));
+
+    let max = Uint128::from(40_000_000 as u64);
+    instantiate.max_contribution_per_address = Some(max);
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr, escrow_addr.clone(), gov_tokens);
+
+    let funding_token_addr: Addr = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::FundingTokenAddr {})
+        .unwrap();
+
+    let fund = |app: &mut App, backer: &str, amount: u64| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+    };
+
+    let err: ContractError = fund(&mut app, "backer_1", 999_999)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::ContributionTooSmall {
+            min: Uint128::from(1_000_000 as u64)
+        }
+    );
+
+    // The cap applies to the total contributed.
+    fund(&mut app, "backer_1", 30_000_000).unwrap();
+    let err: ContractError = fund(&mut app, "backer_1", 20_000_000)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::ContributionLimitExceeded {
+            max,
+            remaining: Uint128::from(10_000_000 as u64)
+        }
+    );
+
+    // Simulations fail the same way.
+    let err = app
+        .wrap()
+        .query_wasm_smart::<SimulateFundResponse>(
+            escrow_addr.clone(),
+            &QueryMsg::SimulateFund {
+                address: "backer_1".to_string(),
+                amount: Uint128::from(20_000_000 as u64),
+                presale: None,
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("At most (10000000) more"));
+
+    // Refunds free up room under the cap.
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        funding_token_addr,
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: Uint128::from(5_000_000 as u64),
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    fund(&mut app, "backer_1", 20_000_000).unwrap();
+
+    // The contribution completing the campaign may be below the
+    // minimum.
+    fund(&mut app, "backer_2", 40_000_000).unwrap();
+    fund(&mut app, "backer_3", 19_500_000).unwrap();
+    fund(&mut app, "backer_3", 500_000).unwrap();
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr, &QueryMsg::DumpState {})
+        .unwrap();
+    assert!(matches!(state.status, Status::Funded { .. }));
+    assert_eq!(state.funds_raised.amount, Uint128::from(funding_goal));
+}
+
+fn presale_leaf(address: &str, allocation: u64) -> [u8; 32] {
+    let mut leaf = [0; 32];
+    leaf.copy_from_slice(&Sha256::digest(
+        format!("{}{}", address, allocation).as_bytes(),
+    ));
+    leaf
+}
+
+#[test]
+fn test_campaign_presale() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2", "backer_3"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    // A two leaf tree allowing backer_1 to contribute 10 and
+    // backer_2 to contribute 20 during the presale.
+    let leaf_1 = presale_leaf("backer_1", 10_000_000);
+    let leaf_2 = presale_leaf("backer_2", 20_000_000);
+    let mut leaves = [leaf_1, leaf_2];
+    leaves.sort_unstable();
+    let merkle_root = hex::encode(Sha256::digest(&leaves.concat()));
+
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr,
+        cw20_id,
+        funding_goal,
+        true,
+    );
+
+    instantiate.presale = Some(Presale {
+        merkle_root: "not a root".to_string(),
+        ends: Expiration::Never {},
+    });
+    let err: ContractError = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidMerkleRoot {});
+
+    let ends = app.block_info().time.plus_seconds(86_400);
+    instantiate.presale = Some(Presale {
+        merkle_root: merkle_root.clone(),
+        ends: Expiration::AtTime(ends),
+    });
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    let fund_presale =
+        |app: &mut App, backer: &str, amount: u64, allocation: u64, proof: Vec<String>| {
+            app.execute_contract(
+                Addr::unchecked(backer),
+                escrow_addr.clone(),
+                &ExecuteMsg::FundPresale {
+                    allocation: Uint128::from(allocation),
+                    proof,
+                },
+                &[Coin {
+                    denom: CHAIN_DENOM.to_string(),
+                    amount: Uint128::from(amount),
+                }],
+            )
+        };
+    let fund = |app: &mut App, backer: &str, amount: u64| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+    };
+
+    // Only allowlisted addresses may contribute during the presale.
+    let err: ContractError = fund(&mut app, "backer_1", 1_000_000)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::PresaleOnly {});
+
+    // Proofs are tied to the address and allocation.
+    let err: ContractError = fund_presale(
+        &mut app,
+        "backer_3",
+        1_000_000,
+        10_000_000,
+        vec![hex::encode(leaf_2)],
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert_eq!(err, ContractError::InvalidProof {});
+    let err: ContractError = fund_presale(
+        &mut app,
+        "backer_1",
+        1_000_000,
+        20_000_000,
+        vec![hex::encode(leaf_2)],
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert_eq!(err, ContractError::InvalidProof {});
+
+    fund_presale(
+        &mut app,
+        "backer_1",
+        6_000_000,
+        10_000_000,
+        vec![hex::encode(leaf_2)],
+    )
+    .unwrap();
+    let err: ContractError = fund_presale(
+        &mut app,
+        "backer_1",
+        6_000_000,
+        10_000_000,
+        vec![hex::encode(leaf_2)],
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert_eq!(
+        err,
+        ContractError::PresaleAllocationExceeded {
+            allocation: Uint128::from(10_000_000 as u64),
+            remaining: Uint128::from(4_000_000 as u64),
+        }
+    );
+
+    // Simulations verify proofs the same way.
+    let simulation: SimulateFundResponse = app
+        .wrap()
+        .query_wasm_smart(
+            escrow_addr.clone(),
+            &QueryMsg::SimulateFund {
+                address: "backer_2".to_string(),
+                amount: Uint128::from(20_000_000 as u64),
+                presale: Some(PresaleProof {
+                    allocation: Uint128::from(20_000_000 as u64),
+                    proof: vec![hex::encode(leaf_1)],
+                }),
+            },
+        )
+        .unwrap();
+    assert_eq!(simulation.accepted, Uint128::from(20_000_000 as u64));
+
+    // Only the DAO may rotate the Merkle root.
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::UpdatePresaleMerkleRoot {
+                merkle_root: hex::encode(leaf_1),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // A single leaf tree whose root is the leaf itself.
+    let leaf_3 = presale_leaf("backer_3", 5_000_000);
+    execute_from_dao(
+        &mut app,
+        dao_addr.clone(),
+        escrow_addr.clone(),
+        &ExecuteMsg::UpdatePresaleMerkleRoot {
+            merkle_root: hex::encode(leaf_3),
+        },
+        2,
+    )
+    .unwrap();
+
+    let err: ContractError = fund_presale(
+        &mut app,
+        "backer_2",
+        1_000_000,
+        20_000_000,
+        vec![hex::encode(leaf_1)],
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert_eq!(err, ContractError::InvalidProof {});
+    fund_presale(&mut app, "backer_3", 5_000_000, 5_000_000, vec![]).unwrap();
+
+    // Anyone may contribute once the presale ends.
+    app.update_block(|block| block.time = ends);
+    fund(&mut app, "backer_2", 89_000_000).unwrap();
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert!(matches!(state.status, Status::Funded { .. }));
+    assert_eq!(state.funds_raised.amount, Uint128::from(funding_goal));
+
+    // The root may not be rotated once the presale has ended.
+    let err: ContractError = execute_from_dao(
+        &mut app,
+        dao_addr,
+        escrow_addr,
+        &ExecuteMsg::UpdatePresaleMerkleRoot {
+            merkle_root: hex::encode(leaf_1),
+        },
+        3,
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert_eq!(err, ContractError::PresaleEnded {});
+}
+
+#[test]
+fn test_campaign_vesting() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked("backer_1"),
+                vec![Coin {
+                    denom: CHAIN_DENOM.to_string(),
+                    amount: Uint128::from(1_000_000_000 as u64),
+                }],
+            )
+            .unwrap();
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr,
+        cw20_id,
+        funding_goal,
+        true,
+    );
+
+    // The cliff may not be longer than the schedule.
+    instantiate.vesting = Some(VestingConfig {
+        cliff: 400_001,
+        duration: 400_000,
+    });
+    let err: ContractError = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::Instantiation(_)));
+
+    instantiate.vesting = Some(VestingConfig {
+        cliff: 100_000,
+        duration: 400_000,
+    });
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr, escrow_addr.clone(), gov_tokens);
+
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: Uint128::from(funding_goal),
+        }],
+    )
+    .unwrap();
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert!(matches!(state.status, Status::Funded { .. }));
+    assert_eq!(state.funded_at, Some(app.block_info().time));
+
+    let gov_balance = |app: &App, address: &str| -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                state.gov_token_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+    let vesting = |app: &App| -> VestingResponse {
+        app.wrap()
+            .query_wasm_smart(
+                escrow_addr.clone(),
+                &QueryMsg::Vesting {
+                    address: "backer_1".to_string(),
+                },
+            )
+            .unwrap()
+    };
+    let claim = |app: &mut App| {
+        app.execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::ClaimVested {},
+            &[],
+        )
+    };
+
+    // Swapped governance tokens are held by the campaign.
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        state.funding_token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: Uint128::from(gov_tokens),
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(gov_balance(&app, "backer_1"), Uint128::zero());
+    assert_eq!(
+        vesting(&app),
+        VestingResponse {
+            total: Uint128::from(gov_tokens),
+            vested: Uint128::zero(),
+            claimed: Uint128::zero(),
+            claimable: Uint128::zero(),
+        }
+    );
+
+    // Nothing may be claimed before the cliff.
+    let err: ContractError = claim(&mut app).unwrap_err().downcast().unwrap();
+    assert_eq!(err, ContractError::NothingToClaim {});
+
+    // A quarter has vested at the cliff.
+    app.update_block(|block| block.time = block.time.plus_seconds(100_000));
+    assert_eq!(vesting(&app).claimable, Uint128::from(12_500_000 as u64));
+    claim(&mut app).unwrap();
+    assert_eq!(
+        gov_balance(&app, "backer_1"),
+        Uint128::from(12_500_000 as u64)
+    );
+    let err: ContractError = claim(&mut app).unwrap_err().downcast().unwrap();
+    assert_eq!(err, ContractError::NothingToClaim {});
+
+    // Everything has vested once the schedule ends.
+    app.update_block(|block| block.time = block.time.plus_seconds(300_000));
+    claim(&mut app).unwrap();
+    assert_eq!(gov_balance(&app, "backer_1"), Uint128::from(gov_tokens));
+    assert_eq!(gov_balance(&app, escrow_addr.as_str()), Uint128::zero());
+    assert_eq!(
+        vesting(&app),
+        VestingResponse {
+            total: Uint128::from(gov_tokens),
+            vested: Uint128::from(gov_tokens),
+            claimed: Uint128::from(gov_tokens),
+            claimable: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn test_campaign_cw_core_dao() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked("backer_1"),
+                vec![Coin {
+                    denom: CHAIN_DENOM.to_string(),
+                    amount: Uint128::from(1_000_000_000 as u64),
+                }],
+            )
+            .unwrap();
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let core_id = app.store_code(mock_cw_core_contract());
+    let voting_id = app.store_code(mock_voting_module_contract());
+    let escrow_id = app.store_code(escrow_contract());
+    let fee_manager_id = app.store_code(fee_manager_contract());
+    let fee_manager_addr = instantiate_fee_manager(&mut app, fee_manager_id);
+
+    let gov_token_addr = app
+        .instantiate_contract(
+            cw20_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Bong DAO".to_string(),
+                symbol: "BDAO".to_string(),
+                decimals: 6,
+                initial_balances: vec![Cw20Coin {
+                    address: CREATOR_ADDR.to_string(),
+                    amount: Uint128::from(gov_tokens),
+                }],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "Bong DAO token",
+            None,
+        )
+        .unwrap();
+    let voting_addr = app
+        .instantiate_contract(
+            voting_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &MockVotingInstantiateMsg {
+                token_contract: gov_token_addr.to_string(),
+                staking_contract: "staking".to_string(),
+            },
+            &[],
+            "Bong DAO voting",
+            None,
+        )
+        .unwrap();
+    let core_addr = app
+        .instantiate_contract(
+            core_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &MockCoreInstantiateMsg {
+                voting_module: voting_addr.to_string(),
+            },
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+    app.execute_contract(
+        Addr::unchecked(CREATOR_ADDR),
+        gov_token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Transfer {
+            recipient: core_addr.to_string(),
+            amount: Uint128::from(gov_tokens),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // The DAO's type must be set to find its governance token.
+    let mut instantiate = instantiate_msg_factory(
+        core_addr.clone(),
+        fee_manager_addr,
+        cw20_id,
+        funding_goal,
+        true,
+    );
+    app.instantiate_contract(
+        escrow_id,
+        Addr::unchecked(CREATOR_ADDR),
+        &instantiate,
+        &[],
+        "Bong DAO",
+        None,
+    )
+    .unwrap_err();
+
+    instantiate.dao_type = Some(DaoType::CwCore {});
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    let gov_token: Addr = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::GovTokenAddr {})
+        .unwrap();
+    assert_eq!(gov_token, gov_token_addr);
+
+    // Seed the campaign from the DAO.
+    let seed: CosmosMsg = WasmMsg::Execute {
+        contract_addr: gov_token_addr.to_string(),
+        msg: to_binary(&cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: Uint128::from(gov_tokens),
+            msg: to_binary("").unwrap(),
+        })
+        .unwrap(),
+        funds: vec![],
+    }
+    .into();
+    app.execute_contract(Addr::unchecked(CREATOR_ADDR), core_addr.clone(), &seed, &[])
+        .unwrap();
+
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: Uint128::from(funding_goal),
+        }],
+    )
+    .unwrap();
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert!(matches!(state.status, Status::Funded { .. }));
+    assert_eq!(state.dao_type, DaoType::CwCore {});
+
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        state.funding_token_addr,
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: Uint128::from(gov_tokens),
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Governance tokens go to the backer and funds less fees to the
+    // DAO.
+    let balance: cw20::BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            gov_token_addr,
+            &cw20::Cw20QueryMsg::Balance {
+                address: "backer_1".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance.balance, Uint128::from(gov_tokens));
+    let dao_balance = app.wrap().query_balance(core_addr, CHAIN_DENOM).unwrap();
+    assert_eq!(dao_balance.amount, Uint128::from(97_000_000 as u64));
+}
+
+#[test]
+fn test_campaign_beneficiaries() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked("backer_1"),
+                vec![Coin {
+                    denom: CHAIN_DENOM.to_string(),
+                    amount: Uint128::from(1_000_000_000 as u64),
+                }],
+            )
+            .unwrap();
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr,
+        cw20_id,
+        funding_goal,
+        true,
+    );
+
+    // Beneficiaries must have a weight, may only be listed once and
+    // their weights must not overflow.
+    for beneficiaries in [
+        vec![BeneficiaryMsg {
+            address: "multisig".to_string(),
+            weight: 0,
+        }],
+        vec![
+            BeneficiaryMsg {
+                address: "multisig".to_string(),
+                weight: 1,
+            },
+            BeneficiaryMsg {
+                address: "multisig".to_string(),
+                weight: 1,
+            },
+        ],
+        vec![
+            BeneficiaryMsg {
+                address: "multisig".to_string(),
+                weight: u64::MAX,
+            },
+            BeneficiaryMsg {
+                address: "grants".to_string(),
+                weight: 1,
+            },
+        ],
+    ] {
+        instantiate.beneficiaries = Some(beneficiaries);
+        let err: ContractError = app
+            .instantiate_contract(
+                escrow_id,
+                Addr::unchecked(CREATOR_ADDR),
+                &instantiate,
+                &[],
+                "Bong DAO",
+                None,
+            )
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert!(matches!(err, ContractError::InvalidBeneficiaries(_)));
+    }
+
+    instantiate.beneficiaries = Some(vec![
+        BeneficiaryMsg {
+            address: "multisig".to_string(),
+            weight: 3,
+        },
+        BeneficiaryMsg {
+            address: "grants".to_string(),
+            weight: 1,
+        },
+    ]);
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: Uint128::from(funding_goal),
+        }],
+    )
+    .unwrap();
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert_eq!(state.beneficiaries.len(), 2);
+
+    let balance = |app: &App, address: &Addr| -> Uint128 {
+        app.wrap()
+            .query_balance(address, CHAIN_DENOM)
+            .unwrap()
+            .amount
+    };
+    let swap = |app: &mut App, tokens: Uint128| {
+        app.execute_contract(
+            Addr::unchecked("backer_1"),
+            state.funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: tokens,
+                msg: to_binary("").unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    };
+
+    // Half the funding tokens are worth 50 of which 3% is taken as a
+    // fee and the rest is split 3:1.
+    let tokens = Uint128::from(25_000_000 as u64);
+    let simulated: SimulateSwapResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::SimulateSwap { tokens })
+        .unwrap();
+    assert_eq!(simulated.dao_amount, Uint128::from(48_500_000 as u64));
+    assert_eq!(
+        simulated.payouts,
+        vec![
+            Payout {
+                address: Addr::unchecked("multisig"),
+                amount: Uint128::from(36_375_000 as u64),
+            },
+            Payout {
+                address: Addr::unchecked("grants"),
+                amount: Uint128::from(12_125_000 as u64),
+            },
+        ]
+    );
+    swap(&mut app, tokens);
+    for payout in simulated.payouts {
+        assert_eq!(balance(&app, &payout.address), payout.amount);
+    }
+
+    // Only the DAO may change its beneficiaries.
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::UpdateBeneficiaries {
+                beneficiaries: vec![],
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Without beneficiaries funds go to the DAO.
+    execute_from_dao(
+        &mut app,
+        dao_addr.clone(),
+        escrow_addr.clone(),
+        &ExecuteMsg::UpdateBeneficiaries {
+            beneficiaries: vec![],
+        },
+        2,
+    )
+    .unwrap();
+    swap(&mut app, tokens);
+    assert_eq!(balance(&app, &dao_addr), Uint128::from(48_500_000 as u64));
+}
+
+#[test]
+fn test_campaign_milestones() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr,
+        cw20_id,
+        funding_goal,
+        true,
+    );
+
+    // Milestones must release all of the funds raised.
+    let mut milestones = MilestoneConfig {
+        milestones: vec![
+            Milestone {
+                description: "Buy the bong".to_string(),
+                release: Decimal::percent(40),
+            },
+            Milestone {
+                description: "Host the party".to_string(),
+                release: Decimal::percent(50),
+            },
+        ],
+        challenge_period: 86_400,
+        veto_threshold: Decimal::percent(50),
+    };
+    instantiate.milestones = Some(milestones.clone());
+    let err: ContractError = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::Instantiation(_)));
+
+    milestones.milestones[1].release = Decimal::percent(60);
+    instantiate.milestones = Some(milestones);
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    for (backer, amount) in [("backer_1", 60_000_000), ("backer_2", 40_000_000)] {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(amount as u64),
+            }],
+        )
+        .unwrap();
+    }
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert!(matches!(state.status, Status::Funded { .. }));
+
+    let native_balance = |app: &App, address: &str| -> Uint128 {
+        app.wrap()
+            .query_balance(address, CHAIN_DENOM)
+            .unwrap()
+            .amount
+    };
+    let send_tokens = |app: &mut App, backer: &str, amount: u64, msg: Binary| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            state.funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: Uint128::from(amount),
+                msg,
+            },
+            &[],
+        )
+    };
+    let vote = to_binary(&ReceiveMsg::Vote {}).unwrap();
+
+    // Swaps hand out governance tokens but funds stay in the
+    // campaign.
+    send_tokens(&mut app, "backer_2", 10_000_000, to_binary("").unwrap()).unwrap();
+    assert_eq!(native_balance(&app, dao_addr.as_str()), Uint128::zero());
+
+    // Only the DAO may claim a milestone.
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::ClaimMilestone {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute_from_dao(
+        &mut app,
+        dao_addr.clone(),
+        escrow_addr.clone(),
+        &ExecuteMsg::ClaimMilestone {},
+        2,
+    )
+    .unwrap();
+
+    let release = |app: &mut App| {
+        app.execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::ReleaseMilestone {},
+            &[],
+        )
+    };
+    let withdraw = |app: &mut App, backer: &str| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::WithdrawVote {},
+            &[],
+        )
+    };
+
+    let err: ContractError = release(&mut app).unwrap_err().downcast().unwrap();
+    assert_eq!(err, ContractError::ChallengePeriodActive {});
+
+    // A quarter of the outstanding funding tokens is not enough to
+    // veto and votes are locked until the poll closes.
+    send_tokens(&mut app, "backer_1", 10_000_000, vote.clone()).unwrap();
+    let err: ContractError = withdraw(&mut app, "backer_1")
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::VoteLocked {});
+
+    // Once the challenge period passes the tranche is released less
+    // fees.
+    app.update_block(|block| block.time = block.time.plus_seconds(86_400));
+    release(&mut app).unwrap();
+    assert_eq!(
+        native_balance(&app, dao_addr.as_str()),
+        Uint128::from(38_800_000 as u64)
+    );
+    let err: ContractError = release(&mut app).unwrap_err().downcast().unwrap();
+    assert_eq!(err, ContractError::NoMilestoneClaim {});
+
+    let err: ContractError = send_tokens(&mut app, "backer_1", 1, vote.clone())
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoPoll {});
+    withdraw(&mut app, "backer_1").unwrap();
+
+    // Holders of half the outstanding funding tokens veto the second
+    // claim.
+    execute_from_dao(
+        &mut app,
+        dao_addr.clone(),
+        escrow_addr.clone(),
+        &ExecuteMsg::ClaimMilestone {},
+        3,
+    )
+    .unwrap();
+    send_tokens(&mut app, "backer_1", 20_000_000, vote).unwrap();
+
+    // The share of the unreleased funds belonging to swapped tokens
+    // is released and the rest is set aside for refunds.
+    let milestones: MilestonesResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::Milestones {})
+        .unwrap();
+    assert_eq!(milestones.progress.completed, 1);
+    assert_eq!(milestones.progress.claim, None);
+    assert_eq!(
+        milestones.progress.vetoed,
+        Some(VetoRefunds {
+            unreleased: Uint128::from(48_000_000 as u64),
+            outstanding: Uint128::from(40_000_000 as u64),
+        })
+    );
+    assert_eq!(
+        native_balance(&app, dao_addr.as_str()),
+        Uint128::from(50_440_000 as u64)
+    );
+
+    let err: ContractError = execute_from_dao(
+        &mut app,
+        dao_addr.clone(),
+        escrow_addr.clone(),
+        &ExecuteMsg::ClaimMilestone {},
+        4,
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert_eq!(err, ContractError::MilestonesVetoed {});
+    let err = app
+        .wrap()
+        .query_wasm_smart::<SimulateSwapResponse, _, _>(
+            escrow_addr.clone(),
+            &QueryMsg::SimulateSwap {
+                tokens: Uint128::from(1 as u64),
+            },
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains(&ContractError::MilestonesVetoed {}.to_string()));
+
+    // Voters withdraw their tokens and every outstanding funding
+    // token is refunded.
+    withdraw(&mut app, "backer_1").unwrap();
+    let refund: SimulateRefundResponse = app
+        .wrap()
+        .query_wasm_smart(
+            escrow_addr.clone(),
+            &QueryMsg::SimulateRefund {
+                address: "backer_1".to_string(),
+                tokens: Uint128::from(30_000_000 as u64),
+            },
+        )
+        .unwrap();
+    assert_eq!(refund.refunded, Uint128::from(36_000_000 as u64));
+    send_tokens(&mut app, "backer_1", 30_000_000, to_binary("").unwrap()).unwrap();
+    send_tokens(&mut app, "backer_2", 10_000_000, to_binary("").unwrap()).unwrap();
+    assert_eq!(
+        native_balance(&app, "backer_1"),
+        Uint128::from(976_000_000 as u64)
+    );
+    assert_eq!(
+        native_balance(&app, "backer_2"),
+        Uint128::from(972_000_000 as u64)
+    );
+    assert_eq!(native_balance(&app, escrow_addr.as_str()), Uint128::zero());
+
+    // Governance tokens that were not swapped are returned to the
+    // DAO.
+    let gov_balance: cw20::BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            state.gov_token_addr,
+            &cw20::Cw20QueryMsg::Balance {
+                address: escrow_addr.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(gov_balance.balance, Uint128::zero());
+}
+
+#[test]
+fn test_campaign_cancellation_poll() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+
+    // Contributors can not cancel campaigns that do not opt in.
+    let escrow_addr = instantiate_escrow(
+        &mut app,
+        dao_addr.clone(),
+        fee_manager_addr.clone(),
+        escrow_id,
+        cw20_id,
+        funding_goal,
+        true,
+    )
+    .unwrap();
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr,
+            &ExecuteMsg::OpenCancellationPoll {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoCancellation {});
+
+    let mut instantiate = instantiate_msg_factory(
+        dao_addr.clone(),
+        fee_manager_addr,
+        cw20_id,
+        funding_goal,
+        true,
+    );
+    instantiate.cancellation = Some(CancellationConfig {
+        quorum: Decimal::zero(),
+        voting_period: 3_600,
+    });
+    let err: ContractError = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, ContractError::Instantiation(_)));
+
+    instantiate.cancellation = Some(CancellationConfig {
+        quorum: Decimal::percent(50),
+        voting_period: 3_600,
+    });
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    for (backer, amount) in [("backer_1", 30_000_000), ("backer_2", 20_000_000)] {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(amount as u64),
+            }],
+        )
+        .unwrap();
+    }
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+
+    let open_poll = |app: &mut App, sender: &str| {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            escrow_addr.clone(),
+            &ExecuteMsg::OpenCancellationPoll {},
+            &[],
+        )
+    };
+    let vote = |app: &mut App, backer: &str, amount: u64| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            state.funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: Uint128::from(amount),
+                msg: to_binary(&ReceiveMsg::Vote {}).unwrap(),
+            },
+            &[],
+        )
+    };
+
+    // Only funding token holders may open a poll and only one may be
+    // open at a time.
+    let err: ContractError = open_poll(&mut app, "backer_3")
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+    open_poll(&mut app, "backer_2").unwrap();
+    let err: ContractError = open_poll(&mut app, "backer_1")
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::PollOpen {});
+
+    // Two fifths of the outstanding funding tokens fall short of the
+    // quorum.
+    vote(&mut app, "backer_2", 10_000_000).unwrap();
+    let status: Status = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert!(matches!(status, Status::Open { .. }));
+
+    vote(&mut app, "backer_1", 5_000_000).unwrap();
+    let poll: Option<Poll> = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::Poll {})
+        .unwrap();
+    let poll = poll.unwrap();
+    assert_eq!(poll.kind, PollKind::Cancel {});
+    assert!(poll.passed);
+    assert_eq!(poll.votes, Uint128::from(15_000_000 as u64));
+
+    // The campaign is cancelled and its governance tokens returned.
+    let status: Status = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert!(matches!(status, Status::Cancelled { .. }));
+    let gov_balance: cw20::BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            state.gov_token_addr.clone(),
+            &cw20::Cw20QueryMsg::Balance {
+                address: escrow_addr.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(gov_balance.balance, Uint128::zero());
+
+    // Voters withdraw their tokens to refund them.
+    app.execute_contract(
+        Addr::unchecked("backer_2"),
+        escrow_addr.clone(),
+        &ExecuteMsg::WithdrawVote {},
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked("backer_2"),
+        state.funding_token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: Uint128::from(10_000_000 as u64),
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    let balance = app
+        .wrap()
+        .query_balance("backer_2", CHAIN_DENOM)
+        .unwrap()
+        .amount;
+    assert_eq!(balance, Uint128::from(1_000_000_000 as u64));
+}
+
+#[test]
+fn test_campaign_fee_snapshot() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked("backer_1"),
+                vec![Coin {
+                    denom: CHAIN_DENOM.to_string(),
+                    amount: Uint128::from(funding_goal as u64),
+                }],
+            )
+            .unwrap();
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+    let escrow_addr = instantiate_escrow(
+        &mut app,
+        dao_addr.clone(),
+        fee_manager_addr.clone(),
+        escrow_id,
+        cw20_id,
+        funding_goal,
+        true,
+    )
+    .unwrap();
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        escrow_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &[Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: Uint128::from(funding_goal as u64),
+        }],
+    )
+    .unwrap();
+
+    let set_fee = |app: &mut App, fee: u64| {
+        app.execute_contract(
+            Addr::unchecked(DAO_UP_ADDR),
+            fee_manager_addr.clone(),
+            &fee_manager::msg::ExecuteMsg::Update {
+                config: fee_manager::msg::ConfigUpdate {
+                    fee: Some(Decimal::percent(fee)),
+                    fee_receiver: None,
+                    public_listing_fee: None,
+                    public_listing_fee_receiver: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    };
+    let simulate_fee = |app: &App| -> Uint128 {
+        let simulated: SimulateSwapResponse = app
+            .wrap()
+            .query_wasm_smart(
+                escrow_addr.clone(),
+                &QueryMsg::SimulateSwap {
+                    tokens: Uint128::from(10_000_000 as u64),
+                },
+            )
+            .unwrap();
+        simulated.fee
+    };
+
+    // Raising the fee after the campaign was created has no effect.
+    set_fee(&mut app, 100);
+    assert_eq!(simulate_fee(&app), Uint128::from(600_000 as u64));
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        state.funding_token_addr,
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: Uint128::from(10_000_000 as u64),
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    let dao_balance = app
+        .wrap()
+        .query_balance(dao_addr.clone(), CHAIN_DENOM)
+        .unwrap()
+        .amount;
+    assert_eq!(dao_balance, Uint128::from(19_400_000 as u64));
+
+    // Only the DAO may accept a reduction and only when one is on
+    // offer.
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::AcceptFeeReduction {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err: ContractError = execute_from_dao(
+        &mut app,
+        dao_addr.clone(),
+        escrow_addr.clone(),
+        &ExecuteMsg::AcceptFeeReduction {},
+        2,
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert_eq!(
+        err,
+        ContractError::NoFeeReduction {
+            fee: Decimal::percent(3)
+        }
+    );
+
+    set_fee(&mut app, 1);
+    execute_from_dao(
+        &mut app,
+        dao_addr,
+        escrow_addr.clone(),
+        &ExecuteMsg::AcceptFeeReduction {},
+        3,
+    )
+    .unwrap();
+    assert_eq!(simulate_fee(&app), Uint128::from(200_000 as u64));
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr, &QueryMsg::DumpState {})
+        .unwrap();
+    assert_eq!(state.fee, Decimal::percent(1));
+    assert_eq!(state.fee_receiver, Addr::unchecked(DAO_UP_ADDR));
+}
+
+#[test]
+fn test_campaign_abort() {
+    let mut app = App::default();
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+    let escrow_addr = instantiate_escrow(
+        &mut app,
+        dao_addr.clone(),
+        fee_manager_addr,
+        escrow_id,
+        cw20_id,
+        100_000_000,
+        true,
+    )
+    .unwrap();
+
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(CREATOR_ADDR),
+            escrow_addr.clone(),
+            &ExecuteMsg::Abort {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // The DAO transfers governance tokens to the campaign instead of
+    // sending them, which leaves it pending.
+    let config: cw3_dao::query::ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(dao_addr.clone(), &cw3_dao::msg::QueryMsg::GetConfig {})
+        .unwrap();
+    let propose_msg = cw3_dao::msg::ExecuteMsg::Propose(cw3_dao::msg::ProposeMsg {
+        title: "Fund the Bong DAO campaign".to_string(),
+        description: "transfer gov tokens to the campaign".to_string(),
+        msgs: vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.gov_token.to_string(),
+            msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                recipient: escrow_addr.to_string(),
+                amount: Uint128::from(1_000_000 as u64),
+            })
+            .unwrap(),
+            funds: vec![],
+        })],
+        latest: None,
+    });
+    for msg in [
+        propose_msg,
+        cw3_dao::msg::ExecuteMsg::Vote(cw3_dao::msg::VoteMsg {
+            proposal_id: 1,
+            vote: cw3::Vote::Yes,
+        }),
+        cw3_dao::msg::ExecuteMsg::Execute { proposal_id: 1 },
+    ] {
+        app.execute_contract(Addr::unchecked(CREATOR_ADDR), dao_addr.clone(), &msg, &[])
+            .unwrap();
+        app.update_block(next_block);
+    }
+
+    let gov_balance = |app: &App, address: &Addr| -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                config.gov_token.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+    assert_eq!(
+        gov_balance(&app, &escrow_addr),
+        Uint128::from(1_000_000 as u64)
+    );
+
+    // Aborting returns the tokens and ends the campaign.
+    execute_from_dao(
+        &mut app,
+        dao_addr.clone(),
+        escrow_addr.clone(),
+        &ExecuteMsg::Abort {},
+        2,
+    )
+    .unwrap();
+    assert_eq!(gov_balance(&app, &escrow_addr), Uint128::zero());
+    assert_eq!(
+        gov_balance(&app, &dao_addr),
+        Uint128::from(100_000_000_000 as u64)
+    );
+
+    let status: Status = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(status, Status::Aborted {});
+
+    let err: ContractError = execute_from_dao(
+        &mut app,
+        dao_addr,
+        escrow_addr.clone(),
+        &ExecuteMsg::Abort {},
+        3,
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert_eq!(err, ContractError::InvalidAbort {});
+
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(CREATOR_ADDR),
+            escrow_addr,
+            &ExecuteMsg::Fund {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NotOpen {});
+}
+
+#[test]
+fn test_campaign_batch_refunds() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 50_000_000;
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2", "backer_3"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+    let escrow_addr = instantiate_escrow(
+        &mut app,
+        dao_addr.clone(),
+        fee_manager_addr,
+        escrow_id,
+        cw20_id,
+        funding_goal,
+        true,
+    )
+    .unwrap();
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+
+    for (backer, amount) in [
+        ("backer_1", 10_000_000),
+        ("backer_2", 20_000_000),
+        ("backer_3", 30_000_000),
+    ] {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(amount as u64),
+            }],
+        )
+        .unwrap();
+    }
+
+    let process_refunds = |app: &mut App| {
+        app.execute_contract(
+            Addr::unchecked("anyone"),
+            escrow_addr.clone(),
+            &ExecuteMsg::ProcessRefunds { limit: Some(2) },
+            &[],
+        )
+    };
+
+    let err: ContractError = process_refunds(&mut app).unwrap_err().downcast().unwrap();
+    assert_eq!(err, ContractError::InvalidProcessRefunds {});
+
+    let return_tokens = |app: &mut App, backer: &str, amount: u64| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            state.funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: Uint128::from(amount),
+                msg: to_binary("").unwrap(),
+            },
+            &[],
+        )
+    };
+    // The funds raised are always what contributors have paid less
+    // their refunds.
+    let assert_ledger_balances = |app: &App| {
+        let page: ContributorsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                escrow_addr.clone(),
+                &QueryMsg::Contributors {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let owed = page
+            .contributors
+            .iter()
+            .fold(Uint128::zero(), |owed, contributor| {
+                owed + contributor.contribution.deposited - contributor.contribution.refunded
+            });
+        let state: DumpStateResponse = app
+            .wrap()
+            .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+            .unwrap();
+        assert_eq!(owed, state.funds_raised.amount);
+    };
+
+    // backer_1 passes on some of its funding tokens which can not be
+    // refunded by their new holder.
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        state.funding_token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Transfer {
+            recipient: "backer_4".to_string(),
+            amount: Uint128::from(2_000_000 as u64),
+        },
+        &[],
+    )
+    .unwrap();
+    let err: ContractError = return_tokens(&mut app, "backer_4", 2_000_000)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::RefundExceedsContribution {});
+    assert_ledger_balances(&app);
+
+    close_escrow_from_dao(&mut app, dao_addr, escrow_addr.clone());
+
+    let native_balance = |app: &App, address: &str| -> Uint128 {
+        app.wrap()
+            .query_balance(address, CHAIN_DENOM)
+            .unwrap()
+            .amount
+    };
+    let token_balance = |app: &App, address: &str| -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                state.funding_token_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+
+    // Contributors are refunded in full without returning their
+    // tokens, including tokens they no longer hold.
+    process_refunds(&mut app).unwrap();
+    for backer in ["backer_1", "backer_2"] {
+        assert_eq!(
+            native_balance(&app, backer),
+            Uint128::from(1_000_000_000 as u64)
+        );
+    }
+    assert_eq!(
+        token_balance(&app, "backer_1"),
+        Uint128::from(3_000_000 as u64)
+    );
+    assert_eq!(
+        token_balance(&app, "backer_2"),
+        Uint128::from(10_000_000 as u64)
+    );
+    assert_eq!(
+        native_balance(&app, "backer_3"),
+        Uint128::from(970_000_000 as u64)
+    );
+    assert_ledger_balances(&app);
+
+    let contribution: Contribution = app
+        .wrap()
+        .query_wasm_smart(
+            escrow_addr.clone(),
+            &QueryMsg::Contribution {
+                address: "backer_2".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(contribution.refunded, Uint128::from(20_000_000 as u64));
+    assert_eq!(contribution.burned, Uint128::from(10_000_000 as u64));
+
+    // Refunded tokens can not be refunded again by their holder or
+    // anyone they are passed on to.
+    for (holder, amount) in [("backer_2", 10_000_000), ("backer_4", 2_000_000)] {
+        let err: ContractError = return_tokens(&mut app, holder, amount)
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(err, ContractError::RefundExceedsContribution {});
+    }
+
+    // Contributors may still refund themselves before their batch
+    // and the next batch refunds what is left.
+    return_tokens(&mut app, "backer_3", 5_000_000).unwrap();
+    assert_eq!(
+        native_balance(&app, "backer_3"),
+        Uint128::from(980_000_000 as u64)
+    );
+    process_refunds(&mut app).unwrap();
+    assert_eq!(
+        native_balance(&app, "backer_3"),
+        Uint128::from(1_000_000_000 as u64)
+    );
+    assert_eq!(
+        token_balance(&app, "backer_3"),
+        Uint128::from(10_000_000 as u64)
+    );
+    assert_eq!(native_balance(&app, escrow_addr.as_str()), Uint128::zero());
+    assert_ledger_balances(&app);
+
+    // Batches after every contributor is refunded do nothing.
+    let res = process_refunds(&mut app).unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attribute| attribute.key == "refunded" && attribute.value == "0")));
+}
+
+#[test]
+fn test_campaign_settle() {
+    let funding_goal = 100_000_000;
+    let gov_tokens = 33_333_333;
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in ["backer_1", "backer_2"] {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::from(1_000_000_000 as u64),
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+    let escrow_addr = instantiate_escrow(
+        &mut app,
+        dao_addr.clone(),
+        fee_manager_addr,
+        escrow_id,
+        cw20_id,
+        funding_goal,
+        true,
+    )
+    .unwrap();
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    for (backer, amount) in [("backer_1", 60_000_000), ("backer_2", 40_000_000)] {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(amount as u64),
+            }],
+        )
+        .unwrap();
+    }
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    let token_balance = |app: &App, token: &Addr, address: &Addr| -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token,
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+    let native_balance = |app: &App, address: &str| -> Uint128 {
+        app.wrap()
+            .query_balance(address, CHAIN_DENOM)
+            .unwrap()
+            .amount
+    };
+
+    // Exchange all of backer 1's funding tokens.
+    let tokens = token_balance(
+        &app,
+        &state.funding_token_addr,
+        &Addr::unchecked("backer_1"),
+    );
+    app.execute_contract(
+        Addr::unchecked("backer_1"),
+        state.funding_token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: tokens,
+            msg: to_binary("").unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_1"),
+            escrow_addr.clone(),
+            &ExecuteMsg::Settle {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Backer 2 still holds funding tokens.
+    let err: ContractError = execute_from_dao(
+        &mut app,
+        dao_addr.clone(),
+        escrow_addr.clone(),
+        &ExecuteMsg::Settle {},
+        2,
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert!(matches!(err, ContractError::InvalidSettle(_)));
+
+    // Once the grace period passes unexchanged funding tokens are
+    // forfeit.
+    app.update_block(|block| block.time = block.time.plus_seconds(60 * 60 * 24 * 365));
+    execute_from_dao(
+        &mut app,
+        dao_addr.clone(),
+        escrow_addr.clone(),
+        &ExecuteMsg::Settle {},
+        3,
+    )
+    .unwrap();
+
+    let status: Status = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(status, Status::Settled {});
+
+    // Everything raised has been paid to the DAO and in fees.
+    assert_eq!(native_balance(&app, escrow_addr.as_str()), Uint128::zero());
+    assert_eq!(
+        native_balance(&app, dao_addr.as_str()) + native_balance(&app, DAO_UP_ADDR),
+        Uint128::from(funding_goal as u64)
+    );
+    assert_eq!(
+        token_balance(&app, &state.gov_token_addr, &escrow_addr),
+        Uint128::zero()
+    );
+
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("backer_2"),
+            state.funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: Uint128::from(1 as u64),
+                msg: to_binary("").unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Settled {});
+}
+
+#[test]
+fn test_pricing_conservation() {
+    // A goal and governance token supply that do not divide each
+    // other so every contribution rounds.
+    let funding_goal = 100_000_007;
+    let gov_tokens = 33_333_331;
+    let contributions = [33_333_334u64, 33_333_335, 33_333_338];
+    let backers: Vec<_> = (0..contributions.len())
+        .map(|i| format!("backer_{}", i))
+        .collect();
+    let backers_for_lambda = backers.clone();
+    let backer_initial_balance = Uint128::from(1_000_000_000 as u64);
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in backers_for_lambda {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: backer_initial_balance,
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let dao_id = app.store_code(dao_dao_dao_contract());
+    let stake_id = app.store_code(stake_cw20_contract());
+    let escrow_id = app.store_code(escrow_contract());
+
+    let (dao_addr, fee_manager_addr) = instantiate_dao(&mut app, dao_id, cw20_id, stake_id);
+    let escrow_addr = instantiate_escrow(
+        &mut app,
+        dao_addr.clone(),
+        fee_manager_addr,
+        escrow_id,
+        cw20_id,
+        funding_goal,
+        true,
+    )
+    .unwrap();
+    fund_escrow_from_dao(&mut app, dao_addr.clone(), escrow_addr.clone(), gov_tokens);
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    let balance_of = |app: &App, token: &Addr, address: &str| {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    };
+    let fund = |app: &mut App, backer: &str, amount: u64| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+        .unwrap();
+    };
+    let send = |app: &mut App, backer: &str, amount: Uint128| {
+        app.execute_contract(
+            Addr::unchecked(backer),
+            state.funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount,
+                msg: to_binary("").unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    };
+
+    // Contributions are minted at the exact ratio rounding down.
+    for (backer, contribution) in backers.iter().zip(contributions.iter()) {
+        fund(&mut app, backer, *contribution);
+        let tokens = balance_of(&app, &state.funding_token_addr, backer);
+        assert_eq!(
+            tokens,
+            Uint128::from(*contribution).multiply_ratio(gov_tokens, funding_goal)
+        );
+
+        // Returning every token refunds the contribution exactly.
+        send(&mut app, backer, tokens);
+        let balance = app.wrap().query_balance(backer, CHAIN_DENOM).unwrap();
+        assert_eq!(balance.amount, backer_initial_balance);
+    }
+    let escrow_balance = app
+        .wrap()
+        .query_balance(escrow_addr.clone(), CHAIN_DENOM)
+        .unwrap();
+    assert_eq!(escrow_balance.amount, Uint128::zero());
+
+    for (backer, contribution) in backers.iter().zip(contributions.iter()) {
+        fund(&mut app, backer, *contribution);
+    }
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert_eq!(
+        state.status,
+        Status::Funded {
+            initial_gov_token_balance: Uint128::from(gov_tokens)
+        }
+    );
+
+    // No more funding tokens are minted than governance tokens are
+    // for sale.
+    let token_info: cw20::TokenInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            state.funding_token_addr.clone(),
+            &cw20::Cw20QueryMsg::TokenInfo {},
+        )
+        .unwrap();
+    assert!(token_info.total_supply <= Uint128::from(gov_tokens));
+
+    // Swapping every funding token pays out no more than was raised.
+    let dao_config: cw3_dao::query::ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(dao_addr.clone(), &cw3_dao::msg::QueryMsg::GetConfig {})
+        .unwrap();
+    let mut swapped = Uint128::zero();
+    for backer in backers.iter() {
+        let tokens = balance_of(&app, &state.funding_token_addr, backer);
+        send(&mut app, backer, tokens);
+        let gov = balance_of(&app, &dao_config.gov_token, backer);
+        assert_eq!(gov, tokens);
+        swapped += gov;
+    }
+    assert_eq!(swapped, token_info.total_supply);
+
+    let dao_balance = app.wrap().query_balance(dao_addr, CHAIN_DENOM).unwrap();
+    let fee_balance = app.wrap().query_balance(DAO_UP_ADDR, CHAIN_DENOM).unwrap();
+    let escrow_balance = app.wrap().query_balance(escrow_addr, CHAIN_DENOM).unwrap();
+    assert!(dao_balance.amount + fee_balance.amount <= Uint128::from(funding_goal));
+    assert_eq!(
+        dao_balance.amount + fee_balance.amount + escrow_balance.amount,
+        Uint128::from(funding_goal)
+    );
+
+    // Ratios at the extremes of Uint128 are computed without
+    // overflowing or losing precision.
+    let max = Uint128::MAX;
+    assert_eq!(mul_ratio(max, max, max).unwrap(), max);
+    assert_eq!(
+        mul_ratio(max, Uint128::from(1u128), Uint128::from(3u128)).unwrap(),
+        Uint128::from(u128::MAX / 3)
+    );
+    assert_eq!(
+        PricingCurve::Fixed {}
+            .tokens_for(
+                Uint128::from(1u128),
+                max,
+                Uint128::zero(),
+                Uint128::from(1u128)
+            )
+            .unwrap(),
+        max
+    );
+    assert!(mul_ratio(max, max, Uint128::from(1u128)).is_err());
+    assert!(mul_ratio(max, max, Uint128::zero()).is_err());
+}
+
+#[test]
+fn test_campaign_large_supply() {
+    // Ten billion governance tokens with 18 decimals raising five
+    // billion native tokens with 6 decimals.
+    let gov_tokens: u128 = 10_000_000_000 * 10u128.pow(18);
+    let funding_goal: u64 = 5_000_000_000 * 1_000_000;
+    let contributions = [1_234_567_890_123_457u128, 3_765_432_109_876_543];
+    let backers: Vec<_> = (0..contributions.len())
+        .map(|i| format!("backer_{}", i))
+        .collect();
+    let backers_for_lambda = backers.clone();
+    let backer_initial_balance = Uint128::from(funding_goal);
+
+    let mut app = App::new(|router, _, storage| {
+        for addr in backers_for_lambda {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(addr),
+                    vec![Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: backer_initial_balance,
+                    }],
+                )
+                .unwrap();
+        }
+    });
+
+    let cw20_id = app.store_code(cw20_contract());
+    let core_id = app.store_code(mock_cw_core_contract());
+    let voting_id = app.store_code(mock_voting_module_contract());
+    let escrow_id = app.store_code(escrow_contract());
+    let fee_manager_id = app.store_code(fee_manager_contract());
+    let fee_manager_addr = instantiate_fee_manager(&mut app, fee_manager_id);
+
+    let gov_token_addr = app
+        .instantiate_contract(
+            cw20_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Bong DAO".to_string(),
+                symbol: "BDAO".to_string(),
+                decimals: 18,
+                initial_balances: vec![Cw20Coin {
+                    address: CREATOR_ADDR.to_string(),
+                    amount: Uint128::from(gov_tokens),
+                }],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "Bong DAO token",
+            None,
+        )
+        .unwrap();
+    let voting_addr = app
+        .instantiate_contract(
+            voting_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &MockVotingInstantiateMsg {
+                token_contract: gov_token_addr.to_string(),
+                staking_contract: "staking".to_string(),
+            },
+            &[],
+            "Bong DAO voting",
+            None,
+        )
+        .unwrap();
+    let core_addr = app
+        .instantiate_contract(
+            core_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &MockCoreInstantiateMsg {
+                voting_module: voting_addr.to_string(),
+            },
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+    app.execute_contract(
+        Addr::unchecked(CREATOR_ADDR),
+        gov_token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Transfer {
+            recipient: core_addr.to_string(),
+            amount: Uint128::from(gov_tokens),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let mut instantiate = instantiate_msg_factory(
+        core_addr.clone(),
+        fee_manager_addr,
+        cw20_id,
+        funding_goal,
+        true,
+    );
+    instantiate.dao_type = Some(DaoType::CwCore {});
+    instantiate.bonus_windows = Some(vec![BonusWindow {
+        until: 60,
+        bonus: Decimal::percent(25),
+    }]);
+    let escrow_addr = app
+        .instantiate_contract(
+            escrow_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &instantiate,
+            &[],
+            "Bong DAO",
+            None,
+        )
+        .unwrap();
+
+    let seed: CosmosMsg = WasmMsg::Execute {
+        contract_addr: gov_token_addr.to_string(),
+        msg: to_binary(&cw20::Cw20ExecuteMsg::Send {
+            contract: escrow_addr.to_string(),
+            amount: Uint128::from(gov_tokens),
+            msg: to_binary("").unwrap(),
+        })
+        .unwrap(),
+        funds: vec![],
+    }
+    .into();
+    app.execute_contract(Addr::unchecked(CREATOR_ADDR), core_addr.clone(), &seed, &[])
+        .unwrap();
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert_eq!(
+        state.status,
+        Status::Open {
+            initial_gov_token_balance: Uint128::from(gov_tokens)
+        }
+    );
+
+    // Four fifths of the governance tokens are sold and the rest pay
+    // the early-bird bonus.
+    let reserve = gov_tokens / 5 * 4;
+    assert_eq!(
+        state.token_price,
+        Some(Decimal::from_ratio(reserve, funding_goal))
+    );
+    let mut minted = Uint128::zero();
+    for (i, (backer, contribution)) in backers.iter().zip(contributions.iter()).enumerate() {
+        // The first backer makes it into the bonus window.
+        if i == 1 {
+            app.update_block(|block| block.time = block.time.plus_seconds(60));
+        }
+        let simulated: SimulateFundResponse = app
+            .wrap()
+            .query_wasm_smart(
+                escrow_addr.clone(),
+                &QueryMsg::SimulateFund {
+                    address: backer.to_string(),
+                    amount: Uint128::from(*contribution),
+                    presale: None,
+                },
+            )
+            .unwrap();
+        app.execute_contract(
+            Addr::unchecked(backer),
+            escrow_addr.clone(),
+            &ExecuteMsg::Fund {},
+            &[Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(*contribution),
+            }],
+        )
+        .unwrap();
+
+        let base = Uint128::from(*contribution)
+            .full_mul(reserve)
+            .checked_div(Uint256::from(funding_goal as u128))
+            .unwrap();
+        let base = Uint128::try_from(base).unwrap();
+        let bonus = if i == 0 {
+            base.multiply_ratio(1u128, 4u128)
+        } else {
+            Uint128::zero()
+        };
+        assert_eq!(simulated.minted, base + bonus);
+        assert_eq!(simulated.bonus, bonus);
+
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                state.funding_token_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: backer.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, base + bonus);
+        minted += balance.balance;
+    }
+    assert!(minted <= Uint128::from(gov_tokens));
+
+    let state: DumpStateResponse = app
+        .wrap()
+        .query_wasm_smart(escrow_addr.clone(), &QueryMsg::DumpState {})
+        .unwrap();
+    assert!(matches!(state.status, Status::Funded { .. }));
+    assert_eq!(state.funds_raised.amount, Uint128::from(funding_goal));
+
+    for backer in backers.iter() {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                state.funding_token_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: backer.to_string(),
+                },
+            )
+            .unwrap();
+        app.execute_contract(
+            Addr::unchecked(backer),
+            state.funding_token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: escrow_addr.to_string(),
+                amount: balance.balance,
+                msg: to_binary("").unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+        let gov: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                gov_token_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: backer.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(gov.balance, balance.balance);
+    }
+
+    // Payouts round down so at most one unit per swap is left
+    // behind.
+    let dao_balance = app.wrap().query_balance(core_addr, CHAIN_DENOM).unwrap();
+    let fee_balance = app.wrap().query_balance(DAO_UP_ADDR, CHAIN_DENOM).unwrap();
+    let paid = dao_balance.amount + fee_balance.amount;
+    assert!(paid <= Uint128::from(funding_goal));
+    assert!(paid + Uint128::from(backers.len() as u128) >= Uint128::from(funding_goal));
+    assert!(fee_balance.amount <= Uint128::from(funding_goal) * Decimal::percent(3));
+}
+
+#[test]
+fn test_pricing_overflow() {
+    let max = Uint128::MAX;
+
+    // Results that do not fit in a Uint128 are errors instead of
+    // panics.
+    assert_eq!(
+        mul_ratio(max, 2u128, 1u128).unwrap_err(),
+        ContractError::Overflow {}
+    );
+    assert_eq!(
+        mul_decimal(max, Decimal::percent(200)).unwrap_err(),
+        ContractError::Overflow {}
+    );
+    assert_eq!(
+        mul_decimal(max, Decimal::percent(50)).unwrap(),
+        max / Uint128::from(2u128)
+    );
+    assert_eq!(
+        PricingCurve::Fixed {}
+            .tokens_for(
+                Uint128::from(1u128),
+                max,
+                Uint128::zero(),
+                Uint128::from(2u128)
+            )
+            .unwrap_err(),
+        ContractError::Overflow {}
+    );
+    let tiers = PricingCurve::Tiers {
+        tiers: vec![Tier {
+            up_to: max,
+            price: Decimal::percent(1_000),
+        }],
+    };
+    assert_eq!(
+        tiers
+            .tokens_for(max, max, Uint128::zero(), max)
+            .unwrap_err(),
+        ContractError::Overflow {}
+    );
+    // So are intermediates that do not fit in 256 bits.
+    let constant_product = PricingCurve::ConstantProduct {
+        virtual_reserve: max,
+    };
+    assert_eq!(
+        constant_product
+            .tokens_for(max, max, Uint128::zero(), max)
+            .unwrap_err(),
+        ContractError::Overflow {}
+    );
+
+    // The reserve held back for bonuses is computed without
+    // overflowing the governance token supply.
+    let windows = [BonusWindow {
+        until: 1,
+        bonus: Decimal::one(),
+    }];
+    assert_eq!(
+        sale_reserve(&windows, max).unwrap(),
+        max / Uint128::from(2u128)
+    );
+}
+
+fn legacy_state(status: crate::migrations::v0_3_0::Status) -> crate::migrations::v0_3_0::State {
+    crate::migrations::v0_3_0::State {
+        status,
+        dao_addr: Addr::unchecked("dao"),
+        fee_manager_addr: Addr::unchecked("fee_manager"),
+        creator: Addr::unchecked(CREATOR_ADDR),
+        funding_goal: Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: Uint128::from(100_000_000 as u64),
+        },
+        funds_raised: Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: Uint128::from(50_000_000 as u64),
+        },
+        campaign_info: instantiate_msg_factory(
+            Addr::unchecked("dao"),
+            Addr::unchecked("fee_manager"),
+            0,
+            100_000_000,
+            true,
+        )
+        .campaign_info,
+    }
+}
+
+/// Migrates campaign storage to the current version against a fee
+/// manager charging a 3% fee.
+fn migrate_storage(storage: &mut MockStorage) -> Result<(), ContractError> {
+    let mut querier: MockQuerier = MockQuerier::new(&[]);
+    querier.update_wasm(|_| {
+        SystemResult::Ok(ContractResult::Ok(
+            to_binary(&fee_manager::msg::ConfigResponse {
+                config: fee_manager::state::Config {
+                    fee: Decimal::percent(3),
+                    fee_receiver: Addr::unchecked(DAO_UP_ADDR),
+                    public_listing_fee: Coin {
+                        denom: CHAIN_DENOM.to_string(),
+                        amount: Uint128::zero(),
+                    },
+                    public_listing_fee_receiver: Addr::unchecked(ANOTHER_DAO_ADDR),
+                },
+            })
+            .unwrap(),
+        ))
+    });
+    crate::migrations::migrate(
+        DepsMut {
+            storage,
+            api: &MockApi::default(),
+            querier: QuerierWrapper::new(&querier),
+        },
+        &mock_env(),
+        "crates.io:cw20-dao-crowdfund",
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
+#[test]
+fn test_migrate_from_v0_3_0() {
+    let mut storage = MockStorage::new();
+    cw2::set_contract_version(&mut storage, "crates.io:cw20-dao-crowdfund", "0.3.0").unwrap();
+
+    let old = legacy_state(crate::migrations::v0_3_0::Status::Open {
+        token_price: Decimal::from_ratio(1u64, 2u64),
+        initial_gov_token_balance: Uint128::from(50_000_000 as u64),
+    });
+    crate::migrations::v0_3_0::STATE
+        .save(&mut storage, &old)
+        .unwrap();
+
+    migrate_storage(&mut storage).unwrap();
+
+    let state = STATE.load(&storage).unwrap();
+    assert_eq!(
+        state.status,
+        Status::Open {
+            initial_gov_token_balance: Uint128::from(50_000_000 as u64),
+        }
+    );
+    assert_eq!(state.dao_addr, old.dao_addr);
+    assert_eq!(
+        state.funding_denom,
+        cw20::Denom::Native(CHAIN_DENOM.to_string())
+    );
+    assert_eq!(state.funds_raised, old.funds_raised);
+    assert_eq!(state.pricing, PricingCurve::Fixed {});
+    assert_eq!(state.fee, Decimal::percent(3));
+    assert_eq!(state.fee_receiver, Addr::unchecked(DAO_UP_ADDR));
+    assert_eq!(state.campaign_info, old.campaign_info);
+    assert_eq!(state.funded_at, None);
+    assert!(state.unrecorded_contributions);
+
+    let version = cw2::get_contract_version(&storage).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // Migrating again to the same version is a no-op.
+    migrate_storage(&mut storage).unwrap();
+    assert_eq!(STATE.load(&storage).unwrap(), state);
+}
+
+#[test]
+fn test_migrate_funded_from_v0_3_0() {
+    let mut storage = MockStorage::new();
+    cw2::set_contract_version(&mut storage, "crates.io:cw20-dao-crowdfund", "0.3.0").unwrap();
+
+    let old = legacy_state(crate::migrations::v0_3_0::Status::Funded {
+        token_price: Decimal::from_ratio(1u64, 2u64),
+        initial_gov_token_balance: Uint128::from(50_000_000 as u64),
+    });
+    crate::migrations::v0_3_0::STATE
+        .save(&mut storage, &old)
+        .unwrap();
+
+    migrate_storage(&mut storage).unwrap();
+
+    // The settlement grace period runs from the migration as when
+    // the campaign was funded is unknown.
+    let state = STATE.load(&storage).unwrap();
+    assert_eq!(
+        state.status,
+        Status::Funded {
+            initial_gov_token_balance: Uint128::from(50_000_000 as u64),
+        }
+    );
+    assert_eq!(state.funded_at, Some(mock_env().block.time));
+}
+
+#[test]
+fn test_migrate_cancelled_from_v0_3_0() {
+    let mut storage = MockStorage::new();
+    cw2::set_contract_version(&mut storage, "crates.io:cw20-dao-crowdfund", "0.3.0").unwrap();
+
+    crate::migrations::v0_3_0::STATE
+        .save(
+            &mut storage,
+            &legacy_state(crate::migrations::v0_3_0::Status::Cancelled {
+                token_price: Decimal::from_ratio(1u64, 2u64),
+                initial_gov_token_balance: Uint128::from(50_000_000 as u64),
+            }),
+        )
+        .unwrap();
+    crate::state::GOV_TOKEN_ADDR
+        .save(&mut storage, &Addr::unchecked("gov_token"))
+        .unwrap();
+    crate::state::FUNDING_TOKEN_ADDR
+        .save(&mut storage, &Addr::unchecked("funding_token"))
+        .unwrap();
+
+    migrate_storage(&mut storage).unwrap();
+    assert!(STATE.load(&storage).unwrap().unrecorded_contributions);
+
+    let querier: MockQuerier = MockQuerier::new(&[]);
+    let execute = |storage: &mut MockStorage, sender: &str, msg: ExecuteMsg| {
+        crate::contract::execute(
+            DepsMut {
+                storage,
+                api: &MockApi::default(),
+                querier: QuerierWrapper::new(&querier),
+            },
+            mock_env(),
+            MessageInfo {
+                sender: Addr::unchecked(sender),
+                funds: vec![],
+            },
+            msg,
+        )
+    };
+
+    // Contributions were not recorded before the migration so any
+    // funding token is refunded at the fixed price.
+    let res = execute(
+        &mut storage,
+        "funding_token",
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "backer_1".to_string(),
+            amount: Uint128::from(10_000_000 as u64),
+            msg: to_binary("").unwrap(),
+        }),
+    )
+    .unwrap();
+    assert!(res.messages.iter().any(|sub| sub.msg
+        == CosmosMsg::Bank(BankMsg::Send {
+            to_address: "backer_1".to_string(),
+            amount: vec![Coin {
+                denom: CHAIN_DENOM.to_string(),
+                amount: Uint128::from(20_000_000 as u64),
+            }],
+        })));
+    assert!(res.messages.iter().any(|sub| sub.msg
+        == CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "funding_token".to_string(),
+            msg: to_binary(&cw20::Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(10_000_000 as u64),
+            })
+            .unwrap(),
+            funds: vec![],
+        })));
+    assert_eq!(
+        STATE.load(&storage).unwrap().funds_raised.amount,
+        Uint128::from(30_000_000 as u64)
+    );
+
+    // Tokens left unburned by a batch could be refunded again.
+    let err = execute(
+        &mut storage,
+        "anyone",
+        ExecuteMsg::ProcessRefunds { limit: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoBatchRefunds {});
+}
+
+#[test]
+fn test_migrate_invalid() {
+    let mut storage = MockStorage::new();
+
+    // Downgrades are not allowed.
+    cw2::set_contract_version(&mut storage, "crates.io:cw20-dao-crowdfund", "99.0.0").unwrap();
+    let err = migrate_storage(&mut storage).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MigrationDowngrade {
+            from: "99.0.0".to_string(),
+            to: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    );
+
+    // Neither is migrating from another contract.
+    cw2::set_contract_version(&mut storage, "crates.io:cw20-base", "0.3.0").unwrap();
+    let err = migrate_storage(&mut storage).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MigrationContractMismatch {
+            contract: "crates.io:cw20-base".to_string(),
+        }
+    );
+
+    // Storage layouts older than 0.3.0 have no migration.
+    for version in ["0.1.0", "0.2.0"] {
+        cw2::set_contract_version(&mut storage, "crates.io:cw20-dao-crowdfund", version).unwrap();
+        let err = migrate_storage(&mut storage).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MigrationUnsupported {
+                from: version.to_string(),
+            }
+        );
+    }
+
+    cw2::set_contract_version(&mut storage, "crates.io:cw20-dao-crowdfund", "latest").unwrap();
+    let err = migrate_storage(&mut storage).unwrap_err();
+    assert_eq!(err, ContractError::InvalidVersion("latest".to_string()));
+}
@@ -0,0 +1,44 @@
+use cosmwasm_std::{Timestamp, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+
+/// A vesting schedule for governance tokens received in exchange for
+/// funding tokens. Tokens vest linearly over `duration` seconds from
+/// when the campaign was funded but none may be claimed until `cliff`
+/// seconds have passed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingConfig {
+    pub cliff: u64,
+    pub duration: u64,
+}
+
+impl VestingConfig {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.duration == 0 {
+            return Err(ContractError::Instantiation(
+                "vesting duration must be non-zero".to_string(),
+            ));
+        }
+        if self.cliff > self.duration {
+            return Err(ContractError::Instantiation(
+                "vesting cliff may not be longer than its duration".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Gets how many of `total` tokens have vested at `now` for a
+    /// schedule that started at `start`.
+    pub fn vested(&self, total: Uint128, start: Timestamp, now: Timestamp) -> Uint128 {
+        let elapsed = now.seconds().saturating_sub(start.seconds());
+        if elapsed < self.cliff {
+            Uint128::zero()
+        } else if elapsed >= self.duration {
+            total
+        } else {
+            total.multiply_ratio(elapsed, self.duration)
+        }
+    }
+}
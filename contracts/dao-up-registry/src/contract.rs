@@ -0,0 +1,336 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Order, Reply,
+    Response, StdResult, SubMsg, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::{Cw20CoinVerified, Cw20ReceiveMsg};
+use cw20_dao_crowdfund::msg::DumpStateResponse;
+use cw20_dao_crowdfund::state::Status;
+use cw_storage_plus::Bound;
+use cw_utils::parse_reply_instantiate_data;
+
+use crate::error::ContractError;
+use crate::msg::{
+    CampaignsResponse, ConfigResponse, ConfigUpdate, ExecuteMsg, InstantiateMsg, QueryMsg,
+    ReceiveMsg,
+};
+use crate::state::{
+    campaigns, CampaignRecord, CampaignStatus, Config, PendingCampaign, State, PENDING_CAMPAIGN,
+    STATE,
+};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:dao-up-registry";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const INSTANTIATE_CAMPAIGN_REPLY_ID: u64 = 0;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let state = State {
+        owner: info.sender,
+        config: Config {
+            campaign_code_id: msg.campaign_code_id,
+        },
+    };
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::default()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", state.owner.to_string())
+        .add_attribute(
+            "campaign_code_id",
+            state.config.campaign_code_id.to_string(),
+        ))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::CreateCampaign { campaign, label } => {
+            create_campaign(deps, env, info.sender, info.funds, campaign, label, None)
+        }
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info.sender, msg),
+        ExecuteMsg::CampaignUpdated { status, hidden } => {
+            execute_campaign_updated(deps, info.sender, status, hidden)
+        }
+        ExecuteMsg::Update { config } => execute_update(deps, info.sender, config),
+    }
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    token: Addr,
+    msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    match from_binary(&msg.msg)? {
+        ReceiveMsg::CreateCampaign { campaign, label } => {
+            // Only the DAO may open its campaign. The campaign
+            // verifies that the tokens sent are the DAO's governance
+            // tokens when it receives them.
+            let dao = deps.api.addr_validate(&campaign.dao_address)?;
+            if msg.sender != dao {
+                return Err(ContractError::Unauthorized {});
+            }
+            create_campaign(
+                deps,
+                env,
+                dao,
+                vec![],
+                campaign,
+                label,
+                Some(Cw20CoinVerified {
+                    address: token,
+                    amount: msg.amount,
+                }),
+            )
+        }
+    }
+}
+
+/// Instantiates a campaign for `creator`. Once it is instantiated
+/// the campaign is listed and, if `seed` is set, sent the governance
+/// tokens in it.
+pub fn create_campaign(
+    deps: DepsMut,
+    env: Env,
+    creator: Addr,
+    funds: Vec<Coin>,
+    mut campaign: Box<cw20_dao_crowdfund::msg::InstantiateMsg>,
+    label: String,
+    seed: Option<Cw20CoinVerified>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let dao = deps.api.addr_validate(&campaign.dao_address)?;
+
+    // The campaign reports changes to its status here.
+    campaign.registry = Some(env.contract.address.to_string());
+
+    PENDING_CAMPAIGN.save(
+        deps.storage,
+        &PendingCampaign {
+            dao: dao.clone(),
+            creator: creator.clone(),
+            seed,
+        },
+    )?;
+
+    let instantiate = WasmMsg::Instantiate {
+        admin: Some(dao.to_string()),
+        code_id: state.config.campaign_code_id,
+        msg: to_binary(&campaign)?,
+        funds,
+        label,
+    };
+
+    Ok(Response::default()
+        .add_attribute("action", "create_campaign")
+        .add_attribute("dao", dao)
+        .add_attribute("creator", creator)
+        .add_submessage(SubMsg::reply_on_success(
+            instantiate,
+            INSTANTIATE_CAMPAIGN_REPLY_ID,
+        )))
+}
+
+pub fn execute_campaign_updated(
+    deps: DepsMut,
+    sender: Addr,
+    status: Status,
+    hidden: bool,
+) -> Result<Response, ContractError> {
+    // Only campaigns created by the registry may update their
+    // listing.
+    let mut campaign = campaigns()
+        .may_load(deps.storage, &sender)?
+        .ok_or(ContractError::Unauthorized {})?;
+    campaign.status = CampaignStatus::from(&status);
+    campaign.hidden = hidden;
+    campaigns().save(deps.storage, &sender, &campaign)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "campaign_updated")
+        .add_attribute("campaign", sender)
+        .add_attribute("hidden", hidden.to_string()))
+}
+
+pub fn execute_update(
+    deps: DepsMut,
+    sender: Addr,
+    config: ConfigUpdate,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(campaign_code_id) = config.campaign_code_id {
+        state.config.campaign_code_id = campaign_code_id;
+    }
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "update")
+        .add_attribute(
+            "campaign_code_id",
+            state.config.campaign_code_id.to_string(),
+        ))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetConfig {} => to_binary(&query_get_config(deps)?),
+        QueryMsg::Campaign { address } => to_binary(&query_campaign(deps, address)?),
+        QueryMsg::ListCampaigns {
+            start_after,
+            limit,
+            status,
+            dao,
+            hidden,
+        } => to_binary(&query_list_campaigns(
+            deps,
+            start_after,
+            limit,
+            status,
+            dao,
+            hidden,
+        )?),
+    }
+}
+
+fn query_get_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(ConfigResponse {
+        config: state.config,
+    })
+}
+
+fn query_campaign(deps: Deps, address: String) -> StdResult<CampaignRecord> {
+    let address = deps.api.addr_validate(&address)?;
+    campaigns().load(deps.storage, &address)
+}
+
+pub fn query_list_campaigns(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    status: Option<CampaignStatus>,
+    dao: Option<String>,
+    hidden: Option<bool>,
+) -> StdResult<CampaignsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let min = start_after
+        .as_ref()
+        .map(|addr| Bound::exclusive(addr.as_str()));
+    let dao = dao.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+
+    // Page over the most selective index that applies and filter
+    // by the remaining fields.
+    let records: Box<dyn Iterator<Item = StdResult<CampaignRecord>> + '_> = match (&dao, &status) {
+        (Some(dao), _) => Box::new(
+            campaigns()
+                .idx
+                .dao
+                .prefix(dao.clone())
+                .range(deps.storage, min, None, Order::Ascending)
+                .map(|item| item.map(|(_, campaign)| campaign)),
+        ),
+        (None, Some(status)) => Box::new(
+            campaigns()
+                .idx
+                .status
+                .prefix(status.as_str().to_string())
+                .range(deps.storage, min, None, Order::Ascending)
+                .map(|item| item.map(|(_, campaign)| campaign)),
+        ),
+        (None, None) => Box::new(
+            campaigns()
+                .range(deps.storage, min, None, Order::Ascending)
+                .map(|item| item.map(|(_, campaign)| campaign)),
+        ),
+    };
+    let campaigns = records
+        .filter(|item| match item {
+            Ok(campaign) => {
+                status.iter().all(|status| &campaign.status == status)
+                    && dao.iter().all(|dao| &campaign.dao == dao)
+                    && hidden.iter().all(|hidden| &campaign.hidden == hidden)
+            }
+            Err(_) => true,
+        })
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CampaignsResponse { campaigns })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_CAMPAIGN_REPLY_ID => {
+            let res = parse_reply_instantiate_data(msg).map_err(|e| {
+                ContractError::Instantiation(format!("failed to instantiate campaign: ({})", e))
+            })?;
+            let address = deps.api.addr_validate(&res.contract_address)?;
+            let PendingCampaign { dao, creator, seed } = PENDING_CAMPAIGN.load(deps.storage)?;
+            PENDING_CAMPAIGN.remove(deps.storage);
+
+            // The campaign has finished instantiating its funding
+            // token by now so ask it for its status.
+            let campaign: DumpStateResponse = deps.querier.query_wasm_smart(
+                address.clone(),
+                &cw20_dao_crowdfund::msg::QueryMsg::DumpState {},
+            )?;
+            campaigns().save(
+                deps.storage,
+                &address,
+                &CampaignRecord {
+                    address: address.clone(),
+                    dao,
+                    creator,
+                    status: CampaignStatus::from(&campaign.status),
+                    hidden: campaign.campaign_info.hidden,
+                },
+            )?;
+
+            let response = Response::default()
+                .add_attribute("method", "reply")
+                .add_attribute("campaign", address.clone());
+            Ok(match seed {
+                Some(seed) => response.add_message(WasmMsg::Execute {
+                    contract_addr: seed.address.to_string(),
+                    msg: to_binary(&cw20::Cw20ExecuteMsg::Send {
+                        contract: address.to_string(),
+                        amount: seed.amount,
+                        msg: Binary::default(),
+                    })?,
+                    funds: vec![],
+                }),
+                None => response,
+            })
+        }
+        _ => Err(ContractError::UnknownReplyId { id: msg.id }),
+    }
+}
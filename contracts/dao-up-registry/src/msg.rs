@@ -0,0 +1,85 @@
+use cw20::Cw20ReceiveMsg;
+use cw20_dao_crowdfund::state::Status;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::state::{CampaignRecord, CampaignStatus, Config};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Code ID of the cw20-dao-crowdfund contract campaigns are
+    /// instantiated from.
+    pub campaign_code_id: u64,
+}
+
+// EXECUTE
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Instantiates a campaign and lists it in the registry. Funds
+    /// sent are forwarded to the campaign to pay for a public
+    /// listing. The DAO is made the campaign's admin.
+    CreateCampaign {
+        campaign: Box<cw20_dao_crowdfund::msg::InstantiateMsg>,
+        label: String,
+    },
+    /// Receives governance tokens sent with a `ReceiveMsg`.
+    Receive(Cw20ReceiveMsg),
+    /// Called by campaigns created by the registry when their status
+    /// or visibility changes.
+    CampaignUpdated { status: Status, hidden: bool },
+    /// Updates the registry's config. Only the owner may do this.
+    Update { config: ConfigUpdate },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Creates a campaign like `ExecuteMsg::CreateCampaign` and opens
+    /// it with the governance tokens sent. This lets a DAO launch a
+    /// campaign in a single proposal. Only the campaign's DAO may
+    /// send this and campaigns listed publicly must not require a
+    /// listing fee as funds can not be sent along with cw20 tokens.
+    CreateCampaign {
+        campaign: Box<cw20_dao_crowdfund::msg::InstantiateMsg>,
+        label: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigUpdate {
+    pub campaign_code_id: Option<u64>,
+}
+
+// QUERY
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    // GetConfig returns the config of the registry.
+    GetConfig {},
+    /// Returns the CampaignRecord for the campaign at `address`.
+    Campaign {
+        address: String,
+    },
+    /// Lists campaigns ordered by address. Only campaigns matching
+    /// every filter set are returned. Returns CampaignsResponse.
+    ListCampaigns {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        status: Option<CampaignStatus>,
+        dao: Option<String>,
+        hidden: Option<bool>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub config: Config,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CampaignsResponse {
+    pub campaigns: Vec<CampaignRecord>,
+}
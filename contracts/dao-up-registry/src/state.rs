@@ -0,0 +1,123 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Addr;
+use cw20::Cw20CoinVerified;
+use cw20_dao_crowdfund::state::Status;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// Code ID of the cw20-dao-crowdfund contract campaigns are
+    /// instantiated from.
+    pub campaign_code_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub owner: Addr,
+    pub config: Config,
+}
+
+/// The stage of a campaign's lifecycle. Mirrors the campaign's own
+/// status without the details of each stage so that listings may be
+/// filtered by it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CampaignStatus {
+    Uninstantiated {},
+    Pending {},
+    Open {},
+    Cancelled {},
+    Funded {},
+    Expired {},
+    Aborted {},
+    Settled {},
+}
+
+impl From<&Status> for CampaignStatus {
+    fn from(status: &Status) -> Self {
+        match status {
+            Status::Uninstantiated {} => CampaignStatus::Uninstantiated {},
+            Status::Pending {} => CampaignStatus::Pending {},
+            Status::Open { .. } => CampaignStatus::Open {},
+            Status::Cancelled { .. } => CampaignStatus::Cancelled {},
+            Status::Funded { .. } => CampaignStatus::Funded {},
+            Status::Expired { .. } => CampaignStatus::Expired {},
+            Status::Aborted {} => CampaignStatus::Aborted {},
+            Status::Settled {} => CampaignStatus::Settled {},
+        }
+    }
+}
+
+impl CampaignStatus {
+    /// The status's name as used by the `status` index.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CampaignStatus::Uninstantiated {} => "uninstantiated",
+            CampaignStatus::Pending {} => "pending",
+            CampaignStatus::Open {} => "open",
+            CampaignStatus::Cancelled {} => "cancelled",
+            CampaignStatus::Funded {} => "funded",
+            CampaignStatus::Expired {} => "expired",
+            CampaignStatus::Aborted {} => "aborted",
+            CampaignStatus::Settled {} => "settled",
+        }
+    }
+}
+
+/// A campaign created by the registry.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CampaignRecord {
+    pub address: Addr,
+    pub dao: Addr,
+    /// The address that asked the registry to create the campaign.
+    pub creator: Addr,
+    /// The campaign's status as last reported by it.
+    pub status: CampaignStatus,
+    pub hidden: bool,
+}
+
+/// A campaign being instantiated, read back once the instantiation
+/// replies with its address.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingCampaign {
+    pub dao: Addr,
+    pub creator: Addr,
+    /// Governance tokens sent to the campaign once it is
+    /// instantiated, opening it.
+    pub seed: Option<Cw20CoinVerified>,
+}
+
+pub const STATE: Item<State> = Item::new("state");
+
+pub struct CampaignIndexes<'a> {
+    pub dao: MultiIndex<'a, (Addr, Vec<u8>), CampaignRecord, Addr>,
+    pub status: MultiIndex<'a, (String, Vec<u8>), CampaignRecord, Addr>,
+}
+
+impl<'a> IndexList<CampaignRecord> for CampaignIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<CampaignRecord>> + '_> {
+        let v: Vec<&dyn Index<CampaignRecord>> = vec![&self.dao, &self.status];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Campaigns created by the registry, indexed by their DAO and
+/// status so that filtered listings only visit matching campaigns.
+pub fn campaigns<'a>() -> IndexedMap<'a, &'a Addr, CampaignRecord, CampaignIndexes<'a>> {
+    let indexes = CampaignIndexes {
+        dao: MultiIndex::new(
+            |campaign, pk| (campaign.dao.clone(), pk),
+            "campaigns",
+            "campaigns__dao",
+        ),
+        status: MultiIndex::new(
+            |campaign, pk| (campaign.status.as_str().to_string(), pk),
+            "campaigns",
+            "campaigns__status",
+        ),
+    };
+    IndexedMap::new("campaigns", indexes)
+}
+pub const PENDING_CAMPAIGN: Item<PendingCampaign> = Item::new("pending_campaign");
@@ -0,0 +1,669 @@
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdResult, Uint128, WasmMsg,
+};
+use cw20::Cw20Coin;
+use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+use cw_storage_plus::Item;
+use serde::{Deserialize, Serialize};
+
+use cw20_dao_crowdfund::dao::DaoType;
+use cw20_dao_crowdfund::msg::DumpStateResponse;
+use cw20_dao_crowdfund::state::Campaign;
+
+use crate::{
+    msg::{
+        CampaignsResponse, ConfigResponse, ConfigUpdate, ExecuteMsg, InstantiateMsg, QueryMsg,
+        ReceiveMsg,
+    },
+    state::{CampaignRecord, CampaignStatus},
+    ContractError,
+};
+
+const OWNER_ADDR: &str = "owner";
+const CREATOR_ADDR: &str = "creator";
+const DAO_UP_ADDR: &str = "daoup";
+const CHAIN_DENOM: &str = "ujunox";
+
+fn cw20_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+fn fee_manager_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        fee_manager::contract::execute,
+        fee_manager::contract::instantiate,
+        fee_manager::contract::query,
+    );
+    Box::new(contract)
+}
+
+fn campaign_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_dao_crowdfund::contract::execute,
+        cw20_dao_crowdfund::contract::instantiate,
+        cw20_dao_crowdfund::contract::query,
+    )
+    .with_reply(cw20_dao_crowdfund::contract::reply);
+    Box::new(contract)
+}
+
+fn registry_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply(crate::contract::reply);
+    Box::new(contract)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct MockDaoInstantiateMsg {
+    token_contract: String,
+}
+
+/// The queries a campaign makes of a cw-core DAO and its voting
+/// module.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum MockDaoQueryMsg {
+    VotingModule {},
+    TokenContract {},
+    StakingContract {},
+}
+
+const MOCK_TOKEN_CONTRACT: Item<Addr> = Item::new("token_contract");
+
+/// A stand-in for a cw-core DAO that is its own voting module and
+/// executes any message sent to it.
+fn mock_dao_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        |_: DepsMut, _: Env, _: MessageInfo, msg: CosmosMsg| -> StdResult<Response> {
+            Ok(Response::default().add_message(msg))
+        },
+        |deps: DepsMut,
+         _: Env,
+         _: MessageInfo,
+         msg: MockDaoInstantiateMsg|
+         -> StdResult<Response> {
+            MOCK_TOKEN_CONTRACT.save(deps.storage, &Addr::unchecked(msg.token_contract))?;
+            Ok(Response::default())
+        },
+        |deps: Deps, env: Env, msg: MockDaoQueryMsg| -> StdResult<Binary> {
+            match msg {
+                MockDaoQueryMsg::VotingModule {} => to_binary(&env.contract.address),
+                MockDaoQueryMsg::TokenContract {} => {
+                    to_binary(&MOCK_TOKEN_CONTRACT.load(deps.storage)?)
+                }
+                MockDaoQueryMsg::StakingContract {} => to_binary(&Addr::unchecked("staking")),
+            }
+        },
+    );
+    Box::new(contract)
+}
+
+struct Suite {
+    app: App,
+    registry: Addr,
+    fee_manager: Addr,
+    cw20_id: u64,
+}
+
+fn setup() -> Suite {
+    let mut app = App::default();
+    let cw20_id = app.store_code(cw20_contract());
+    let fee_manager_id = app.store_code(fee_manager_contract());
+    let campaign_id = app.store_code(campaign_contract());
+    let registry_id = app.store_code(registry_contract());
+
+    // Public listings are free so campaigns may be created without
+    // funds.
+    let fee_manager = app
+        .instantiate_contract(
+            fee_manager_id,
+            Addr::unchecked(DAO_UP_ADDR),
+            &fee_manager::msg::InstantiateMsg {
+                fee: Decimal::percent(3),
+                fee_receiver: DAO_UP_ADDR.to_string(),
+                public_listing_fee: Coin {
+                    denom: CHAIN_DENOM.to_string(),
+                    amount: Uint128::zero(),
+                },
+                public_listing_fee_receiver: DAO_UP_ADDR.to_string(),
+            },
+            &[],
+            "DAO Up! fee manager",
+            None,
+        )
+        .unwrap();
+
+    let registry = app
+        .instantiate_contract(
+            registry_id,
+            Addr::unchecked(OWNER_ADDR),
+            &InstantiateMsg {
+                campaign_code_id: campaign_id,
+            },
+            &[],
+            "DAO Up! registry",
+            None,
+        )
+        .unwrap();
+
+    Suite {
+        app,
+        registry,
+        fee_manager,
+        cw20_id,
+    }
+}
+
+/// Creates a DAO holding `gov_tokens` of a new governance token.
+/// Returns the addresses of the DAO and its token.
+fn instantiate_dao(suite: &mut Suite, name: &str, gov_tokens: u128) -> (Addr, Addr) {
+    let token = suite
+        .app
+        .instantiate_contract(
+            suite.cw20_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &cw20_base::msg::InstantiateMsg {
+                name: name.to_string(),
+                symbol: "BDAO".to_string(),
+                decimals: 6,
+                initial_balances: vec![Cw20Coin {
+                    address: CREATOR_ADDR.to_string(),
+                    amount: Uint128::from(gov_tokens),
+                }],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            name,
+            None,
+        )
+        .unwrap();
+    let dao_id = suite.app.store_code(mock_dao_contract());
+    let dao = suite
+        .app
+        .instantiate_contract(
+            dao_id,
+            Addr::unchecked(CREATOR_ADDR),
+            &MockDaoInstantiateMsg {
+                token_contract: token.to_string(),
+            },
+            &[],
+            name,
+            None,
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(CREATOR_ADDR),
+            token.clone(),
+            &cw20::Cw20ExecuteMsg::Transfer {
+                recipient: dao.to_string(),
+                amount: Uint128::from(gov_tokens),
+            },
+            &[],
+        )
+        .unwrap();
+    (dao, token)
+}
+
+fn campaign_msg(
+    suite: &Suite,
+    dao: &Addr,
+    hidden: bool,
+) -> cw20_dao_crowdfund::msg::InstantiateMsg {
+    cw20_dao_crowdfund::msg::InstantiateMsg {
+        dao_address: dao.to_string(),
+        dao_type: Some(DaoType::CwCore {}),
+        fee_manager_address: suite.fee_manager.to_string(),
+        beneficiaries: None,
+        cw20_code_id: suite.cw20_id,
+        funding_goal: Coin {
+            denom: CHAIN_DENOM.to_string(),
+            amount: Uint128::from(100_000_000u128),
+        },
+        funding_cw20: None,
+        min_goal: None,
+        pricing: None,
+        bonus_windows: None,
+        min_contribution: None,
+        max_contribution_per_address: None,
+        presale: None,
+        vesting: None,
+        milestones: None,
+        cancellation: None,
+        registry: None,
+        funding_token_name: "Bong Launch".to_string(),
+        funding_token_symbol: "LBONG".to_string(),
+        deadline: None,
+        campaign_info: campaign_info(hidden),
+    }
+}
+
+fn campaign_info(hidden: bool) -> Campaign {
+    Campaign {
+        name: "Bong DAO".to_string(),
+        description: "We're raising money to buy a bong!".to_string(),
+        website: None,
+        twitter: None,
+        discord: None,
+        profile_image_url: None,
+        description_image_urls: vec![],
+        hidden,
+    }
+}
+
+/// Gets the address of the campaign created in `res`.
+fn created_campaign(res: &AppResponse) -> Addr {
+    res.events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == "campaign")
+        .map(|attribute| Addr::unchecked(&attribute.value))
+        .unwrap()
+}
+
+fn create_campaign(suite: &mut Suite, dao: &Addr, hidden: bool) -> Addr {
+    let campaign = campaign_msg(suite, dao, hidden);
+    let res = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(CREATOR_ADDR),
+            suite.registry.clone(),
+            &ExecuteMsg::CreateCampaign {
+                campaign: Box::new(campaign),
+                label: "Bong DAO campaign".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    created_campaign(&res)
+}
+
+fn list_campaigns(
+    suite: &Suite,
+    start_after: Option<&Addr>,
+    limit: Option<u32>,
+    status: Option<CampaignStatus>,
+    dao: Option<&Addr>,
+    hidden: Option<bool>,
+) -> Vec<Addr> {
+    let res: CampaignsResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            suite.registry.clone(),
+            &QueryMsg::ListCampaigns {
+                start_after: start_after.map(|addr| addr.to_string()),
+                limit,
+                status,
+                dao: dao.map(|addr| addr.to_string()),
+                hidden,
+            },
+        )
+        .unwrap();
+    res.campaigns
+        .into_iter()
+        .map(|campaign| campaign.address)
+        .collect()
+}
+
+fn query_campaign(suite: &Suite, campaign: &Addr) -> CampaignRecord {
+    suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            suite.registry.clone(),
+            &QueryMsg::Campaign {
+                address: campaign.to_string(),
+            },
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_create_and_list_campaigns() {
+    let mut suite = setup();
+    let (dao_a, _) = instantiate_dao(&mut suite, "DAO A", 100_000_000);
+    let (dao_b, _) = instantiate_dao(&mut suite, "DAO B", 100_000_000);
+
+    let public_a = create_campaign(&mut suite, &dao_a, false);
+    let hidden_a = create_campaign(&mut suite, &dao_a, true);
+    let public_b = create_campaign(&mut suite, &dao_b, false);
+
+    assert_eq!(
+        query_campaign(&suite, &public_a),
+        CampaignRecord {
+            address: public_a.clone(),
+            dao: dao_a.clone(),
+            creator: Addr::unchecked(CREATOR_ADDR),
+            status: CampaignStatus::Pending {},
+            hidden: false,
+        }
+    );
+
+    // Campaigns report back to the registry that created them and
+    // are administered by their DAO.
+    let state: DumpStateResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            public_a.clone(),
+            &cw20_dao_crowdfund::msg::QueryMsg::DumpState {},
+        )
+        .unwrap();
+    assert_eq!(state.registry, Some(suite.registry.clone()));
+    let data = suite.app.contract_data(&public_a).unwrap();
+    assert_eq!(data.admin, Some(dao_a.clone()));
+
+    let mut all = vec![public_a.clone(), hidden_a.clone(), public_b.clone()];
+    all.sort();
+    assert_eq!(list_campaigns(&suite, None, None, None, None, None), all);
+
+    // Filters.
+    let mut by_dao_a = vec![public_a.clone(), hidden_a.clone()];
+    by_dao_a.sort();
+    assert_eq!(
+        list_campaigns(&suite, None, None, None, Some(&dao_a), None),
+        by_dao_a
+    );
+    assert_eq!(
+        list_campaigns(&suite, None, None, None, None, Some(true)),
+        vec![hidden_a.clone()]
+    );
+    assert_eq!(
+        list_campaigns(&suite, None, None, None, Some(&dao_b), Some(false)),
+        vec![public_b]
+    );
+    assert_eq!(
+        list_campaigns(
+            &suite,
+            None,
+            None,
+            Some(CampaignStatus::Pending {}),
+            None,
+            None
+        )
+        .len(),
+        3
+    );
+    assert!(list_campaigns(
+        &suite,
+        None,
+        None,
+        Some(CampaignStatus::Open {}),
+        None,
+        None
+    )
+    .is_empty());
+
+    // Pagination.
+    let first = list_campaigns(&suite, None, Some(2), None, None, None);
+    assert_eq!(first, all[0..2].to_vec());
+    let rest = list_campaigns(&suite, Some(&first[1]), Some(2), None, None, None);
+    assert_eq!(rest, all[2..].to_vec());
+    let pending = list_campaigns(
+        &suite,
+        Some(&all[0]),
+        Some(1),
+        Some(CampaignStatus::Pending {}),
+        None,
+        None,
+    );
+    assert_eq!(pending, all[1..2].to_vec());
+    let filtered = list_campaigns(&suite, Some(&all[0]), Some(1), None, Some(&dao_a), None);
+    assert_eq!(
+        filtered,
+        by_dao_a
+            .into_iter()
+            .filter(|addr| addr > &all[0])
+            .take(1)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_campaign_status_callbacks() {
+    let mut suite = setup();
+    let (dao, token) = instantiate_dao(&mut suite, "DAO A", 100_000_000);
+    let campaign = create_campaign(&mut suite, &dao, false);
+
+    // Seeding the campaign opens it.
+    let seed: CosmosMsg = WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_binary(&cw20::Cw20ExecuteMsg::Send {
+            contract: campaign.to_string(),
+            amount: Uint128::from(50_000_000u128),
+            msg: to_binary("").unwrap(),
+        })
+        .unwrap(),
+        funds: vec![],
+    }
+    .into();
+    suite
+        .app
+        .execute_contract(Addr::unchecked(CREATOR_ADDR), dao.clone(), &seed, &[])
+        .unwrap();
+    assert_eq!(
+        query_campaign(&suite, &campaign).status,
+        CampaignStatus::Open {}
+    );
+
+    // Visibility changes are reported.
+    let hide: CosmosMsg = WasmMsg::Execute {
+        contract_addr: campaign.to_string(),
+        msg: to_binary(&cw20_dao_crowdfund::msg::ExecuteMsg::UpdateCampaign {
+            campaign: campaign_info(true),
+        })
+        .unwrap(),
+        funds: vec![],
+    }
+    .into();
+    suite
+        .app
+        .execute_contract(Addr::unchecked(CREATOR_ADDR), dao.clone(), &hide, &[])
+        .unwrap();
+    let record = query_campaign(&suite, &campaign);
+    assert_eq!(record.status, CampaignStatus::Open {});
+    assert!(record.hidden);
+
+    let close: CosmosMsg = WasmMsg::Execute {
+        contract_addr: campaign.to_string(),
+        msg: to_binary(&cw20_dao_crowdfund::msg::ExecuteMsg::Close {}).unwrap(),
+        funds: vec![],
+    }
+    .into();
+    suite
+        .app
+        .execute_contract(Addr::unchecked(CREATOR_ADDR), dao, &close, &[])
+        .unwrap();
+    assert_eq!(
+        list_campaigns(
+            &suite,
+            None,
+            None,
+            Some(CampaignStatus::Cancelled {}),
+            None,
+            Some(true)
+        ),
+        vec![campaign]
+    );
+    assert!(list_campaigns(
+        &suite,
+        None,
+        None,
+        Some(CampaignStatus::Open {}),
+        None,
+        None
+    )
+    .is_empty());
+
+    // Only campaigns created by the registry may report changes.
+    let err: ContractError = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(CREATOR_ADDR),
+            suite.registry.clone(),
+            &ExecuteMsg::CampaignUpdated {
+                status: cw20_dao_crowdfund::state::Status::Aborted {},
+                hidden: false,
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_create_and_seed_campaign() {
+    let mut suite = setup();
+    let (dao, token) = instantiate_dao(&mut suite, "DAO A", 100_000_000);
+
+    // Only the DAO may create and seed its campaign.
+    suite
+        .app
+        .execute_contract(
+            dao.clone(),
+            token.clone(),
+            &cw20::Cw20ExecuteMsg::Transfer {
+                recipient: CREATOR_ADDR.to_string(),
+                amount: Uint128::from(50_000_000u128),
+            },
+            &[],
+        )
+        .unwrap();
+    let err: ContractError = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(CREATOR_ADDR),
+            token.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: suite.registry.to_string(),
+                amount: Uint128::from(50_000_000u128),
+                msg: to_binary(&ReceiveMsg::CreateCampaign {
+                    campaign: Box::new(campaign_msg(&suite, &dao, false)),
+                    label: "Bong DAO campaign".to_string(),
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // A single message from the DAO opens the campaign.
+    let msg: CosmosMsg = WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_binary(&cw20::Cw20ExecuteMsg::Send {
+            contract: suite.registry.to_string(),
+            amount: Uint128::from(50_000_000u128),
+            msg: to_binary(&ReceiveMsg::CreateCampaign {
+                campaign: Box::new(campaign_msg(&suite, &dao, false)),
+                label: "Bong DAO campaign".to_string(),
+            })
+            .unwrap(),
+        })
+        .unwrap(),
+        funds: vec![],
+    }
+    .into();
+    let res = suite
+        .app
+        .execute_contract(Addr::unchecked(CREATOR_ADDR), dao.clone(), &msg, &[])
+        .unwrap();
+    let campaign = created_campaign(&res);
+    assert_eq!(
+        query_campaign(&suite, &campaign),
+        CampaignRecord {
+            address: campaign.clone(),
+            dao: dao.clone(),
+            creator: dao.clone(),
+            status: CampaignStatus::Open {},
+            hidden: false,
+        }
+    );
+
+    let state: DumpStateResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            campaign.clone(),
+            &cw20_dao_crowdfund::msg::QueryMsg::DumpState {},
+        )
+        .unwrap();
+    assert!(matches!(
+        state.status,
+        cw20_dao_crowdfund::state::Status::Open {
+            initial_gov_token_balance
+        } if initial_gov_token_balance == Uint128::from(50_000_000u128)
+    ));
+    let balance = |address: &Addr| -> Uint128 {
+        let res: cw20::BalanceResponse = suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                token.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.balance
+    };
+    assert_eq!(balance(&campaign), Uint128::from(50_000_000u128));
+    assert_eq!(balance(&dao), Uint128::zero());
+    assert_eq!(balance(&suite.registry), Uint128::zero());
+}
+
+#[test]
+fn test_update_config() {
+    let mut suite = setup();
+
+    let update = ExecuteMsg::Update {
+        config: ConfigUpdate {
+            campaign_code_id: Some(42),
+        },
+    };
+    let err: ContractError = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(CREATOR_ADDR),
+            suite.registry.clone(),
+            &update,
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER_ADDR),
+            suite.registry.clone(),
+            &update,
+            &[],
+        )
+        .unwrap();
+    let config: ConfigResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(suite.registry.clone(), &QueryMsg::GetConfig {})
+        .unwrap();
+    assert_eq!(config.config.campaign_code_id, 42);
+}
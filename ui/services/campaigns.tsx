@@ -106,6 +106,7 @@ export const transformVersionedCampaignFields = (
       }
     case CampaignContractVersion.v2:
     case CampaignContractVersion.v3:
+    case CampaignContractVersion.v4:
       return {
         feeManagerAddress: campaignState.fee_manager_addr ?? null,
         profileImageUrl: campaignInfo.profile_image_url ?? null,
@@ -195,6 +196,15 @@ export const transformCampaign = (
   )
     ? (stateVersion as CampaignContractVersion)
     : CampaignContractVersion.v1
+  // Funding tokens per pay token. Moved out of the status in v4, where it is
+  // only set for fixed price campaigns.
+  const tokenPrice =
+    status === CampaignStatus.Pending
+      ? null
+      : version === CampaignContractVersion.v4
+      ? state.token_price ?? null
+      : statusFields.token_price ?? null
+
   // Get fields based on contract version.
   const versionedFields = transformVersionedCampaignFields(
     version,
@@ -256,30 +266,15 @@ export const transformCampaign = (
       // Convert to supply.
       total_supply: undefined,
 
-      ...(status !== CampaignStatus.Pending
+      ...(tokenPrice !== null
         ? {
             // Funding tokens are minted on-demand, so calculate the total that will ever exist
             // by multiplying the price of one token (in payToken) by the goal (in payToken).
             supply: convertMicroDenomToDenom(
-              Number(state.funding_goal.amount) *
-                Number(
-                  (
-                    statusFields as CampaignVersionedStatus<
-                      typeof version,
-                      typeof status
-                    >
-                  ).token_price
-                ),
+              Number(state.funding_goal.amount) * Number(tokenPrice),
               fundingTokenInfo.decimals
             ),
-            price: Number(
-              (
-                statusFields as CampaignVersionedStatus<
-                  typeof version,
-                  typeof status
-                >
-              ).token_price
-            ),
+            price: Number(tokenPrice),
           }
         : {
             supply: null,
@@ -113,6 +113,7 @@ export enum CampaignContractVersion {
   v1 = "0.1.0",
   v2 = "0.2.0",
   v3 = "0.3.0",
+  v4 = "0.4.0",
 }
 
 export enum CampaignStatus {
@@ -138,6 +139,12 @@ export type CampaignVersionedStatus<
         token_price: string
         initial_gov_token_balance: string
       }
+  : V extends CampaignContractVersion.v4
+  ? S extends CampaignStatus.Pending
+    ? {}
+    : {
+        initial_gov_token_balance: string
+      }
   : {}
 
 export enum CampaignActionType {
@@ -22,6 +22,7 @@ declare global {
     gov_token_addr: string
     gov_token_info?: TokenInfoResponse
     status: CampaignDumpStateStatus
+    token_price?: string | null // v4
     version?: string
   }
 